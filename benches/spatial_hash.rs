@@ -0,0 +1,91 @@
+//! Compares looking up the colliders around the crow through `SpatialHash` with checking
+//! every collider, for levels with more and more colliders in them.
+//!
+//! Run with `cargo bench --bench spatial_hash`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use crow_jewels::SpatialHash;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const LOOKUPS: usize = 10_000;
+// square pixels of level per collider
+const DENSITY: f32 = 200.0 * 200.0;
+const CROW_SIZE: Vec2 = bevy::math::const_vec2!([60.0, 60.0]);
+
+fn overlaps(a: Vec2, a_size: Vec2, b: Vec2, b_size: Vec2) -> bool {
+    let reach = (a_size + b_size) / 2.0;
+    let distance = (a - b).abs();
+    distance.x < reach.x && distance.y < reach.y
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..LOOKUPS {
+        f();
+    }
+    start.elapsed() / LOOKUPS as u32
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(42);
+    println!(
+        "{:>10} {:>14} {:>14}",
+        "colliders", "every collider", "spatial hash"
+    );
+    for count in [100, 1_000, 5_000, 10_000, 50_000] {
+        // colliders the size of jewels, as crowded as a busy stretch of the shipped level, in a
+        // level that grows wider the more of them there are
+        let half_width = count as f32 * DENSITY / 600.0 / 2.0;
+        let colliders: Vec<(Entity, Vec2, Vec2)> = (0..count)
+            .map(|i| {
+                let position = Vec2::new(
+                    rng.gen_range(-half_width..half_width),
+                    rng.gen_range(0.0..600.0),
+                );
+                (Entity::from_raw(i), position, Vec2::new(64.0, 64.0))
+            })
+            .collect();
+        let mut spatial_hash = SpatialHash::default();
+        for (entity, position, size) in colliders.iter() {
+            spatial_hash.insert(*entity, *position, *size);
+        }
+        let crows: Vec<Vec2> = (0..LOOKUPS)
+            .map(|_| {
+                Vec2::new(
+                    rng.gen_range(-half_width..half_width),
+                    rng.gen_range(0.0..600.0),
+                )
+            })
+            .collect();
+
+        let mut crow = crows.iter().cycle();
+        let brute_force = time(|| {
+            let crow = *crow.next().unwrap();
+            black_box(
+                colliders
+                    .iter()
+                    .filter(|(_, position, size)| overlaps(crow, CROW_SIZE, *position, *size))
+                    .count(),
+            );
+        });
+        let mut crow = crows.iter().cycle();
+        let hashed = time(|| {
+            let crow = *crow.next().unwrap();
+            black_box(
+                spatial_hash
+                    .query(crow, CROW_SIZE)
+                    .into_iter()
+                    .filter(|entity| {
+                        let (_, position, size) = colliders[entity.id() as usize];
+                        overlaps(crow, CROW_SIZE, position, size)
+                    })
+                    .count(),
+            );
+        });
+        println!("{:>10} {:>14?} {:>14?}", count, brute_force, hashed);
+    }
+}
@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+use bevy_kira_audio::{Audio, AudioChannel};
+
+use crate::crow::CrowFlapped;
+use crate::noise::{Noise, NoiseKind};
+
+struct WingAudioChannel(AudioChannel);
+
+struct FootstepAudioChannel(AudioChannel);
+
+/// Background music and sound effects. Requires `bevy_kira_audio::AudioPlugin`.
+pub struct AudioFxPlugin;
+
+impl Plugin for AudioFxPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WingAudioChannel(AudioChannel::new("wings".to_owned())))
+            .insert_resource(FootstepAudioChannel(AudioChannel::new(
+                "footsteps".to_owned(),
+            )))
+            .add_startup_system(play_music)
+            .add_system(play_wing_flaps)
+            .add_system(play_footsteps);
+    }
+}
+
+fn play_music(audio: Res<Audio>, asset_server: Res<AssetServer>) {
+    audio.play_looped(asset_server.load("AcesHighKevinMacleod.ogg"));
+    audio.set_volume(0.3);
+}
+
+fn play_wing_flaps(
+    mut flap_events: EventReader<CrowFlapped>,
+    audio: Res<Audio>,
+    asset_server: Res<AssetServer>,
+    wing_channel: Res<WingAudioChannel>,
+) {
+    for _ in flap_events.iter() {
+        audio.play_in_channel(asset_server.load("wingflap.wav"), &wing_channel.0);
+    }
+}
+
+fn play_footsteps(
+    mut noise_events: EventReader<Noise>,
+    audio: Res<Audio>,
+    asset_server: Res<AssetServer>,
+    footstep_channel: Res<FootstepAudioChannel>,
+) {
+    for noise in noise_events.iter() {
+        if noise.kind == NoiseKind::Footstep {
+            audio.play_in_channel(asset_server.load("footstep.wav"), &footstep_channel.0);
+        }
+    }
+}
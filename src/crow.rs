@@ -0,0 +1,724 @@
+use bevy::prelude::*;
+use bevy::sprite::collide_aabb::collide;
+
+use crate::jewel::Jewel;
+use crate::level::{CurrentLevel, Level};
+use crate::physics::{Contacts, KinematicBody, PhysicsSystem, CONTACT_SKIN};
+use crate::spatial::SpatialHash;
+use crate::state::{GameState, GameplayTick, TIME_STEP};
+use crate::world::{AnimationTimer, Background, BirdCamera, Collider, ColliderType, RunEntity};
+
+// size of the crow's collision box
+pub(crate) const CROW_SIZE: Vec2 = bevy::math::const_vec2!([60.0, 60.0]);
+
+// the speed a crow is sent flying with when a person catches it
+const KNOCKBACK: Vec2 = bevy::math::const_vec2!([300.0, 300.0]);
+// seconds the crow cannot be caught again after being caught
+const INVULNERABILITY_TIME: f32 = 1.5;
+// seconds between the crow's sprite flashing on and off while it cannot be caught
+const FLASH_INTERVAL: f32 = 0.1;
+// the upward speed a crow pulls out of a dive with after snatching a jewel
+const SNATCH_PULL_UP: f32 = 250.0;
+
+#[derive(Component)]
+pub struct Crow {
+    pub crow_state: CrowState,
+    idle_frame_tick_times: Vec<usize>,
+    fly_frame_tick_times: Vec<usize>,
+    glide_frame_tick_times: Vec<usize>,
+    dive_frame_tick_times: Vec<usize>,
+    run_frame_tick_times: Vec<usize>,
+    pickup_frame_tick_times: Vec<usize>,
+    idle_frame_tick_counter: usize,
+    // whether flap was held last tick, a flap only happens when it is first pressed
+    flap_held: bool,
+    // seconds until the crow can flap again
+    flap_cooldown: f32,
+    /// The jewel the crow is holding on to, it only scores once it is dropped in a nest.
+    pub carrying: Option<Entity>,
+    pub score: usize,
+}
+
+/// How many more times the crow can be caught before the run is over.
+#[derive(Component)]
+pub struct Health {
+    pub lives: u32,
+    /// Seconds left during which the crow cannot be caught again.
+    pub invulnerable: f32,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            lives: 3,
+            invulnerable: 0.0,
+        }
+    }
+}
+
+/// How much flying the crow has left in it. Flapping and staying in the air spend it, standing
+/// on a surface wins it back.
+#[derive(Component)]
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            current: 100.0,
+            max: 100.0,
+        }
+    }
+}
+
+/// Marks a jewel the crow is holding. It has no `Collider` while it is carried.
+#[derive(Component)]
+pub struct Carried;
+
+/// How the crow handles, speeds in pixels per second and accelerations in pixels per second
+/// squared.
+#[derive(Component, Clone, Copy)]
+pub struct CrowFlight {
+    /// The upward speed a flap gives the crow.
+    pub flap_impulse: f32,
+    /// Seconds after a flap during which pressing flap again does nothing.
+    pub flap_cooldown: f32,
+    /// How fast the crow falls at most while gliding, `None` to not glide at all.
+    pub glide_fall_speed: Option<f32>,
+    /// How much faster than `max_speed` the crow can go sideways while gliding.
+    pub glide_speed_boost: f32,
+    /// How fast the crow swoops down in a dive, straight away rather than picking up speed like
+    /// in a fall. The body's terminal velocity still caps it.
+    pub dive_speed: f32,
+    pub max_speed: f32,
+    pub ground_acceleration: f32,
+    pub air_acceleration: f32,
+    /// How quickly the crow slows down when not steering.
+    pub ground_drag: f32,
+    pub air_drag: f32,
+    /// How much of its top speed and flap the crow loses carrying a jewel, for each unit of the
+    /// jewel's weight.
+    pub carry_slowdown: f32,
+    /// The stamina a flap costs, the crow cannot flap with less than this left.
+    pub flap_stamina: f32,
+    /// Stamina lost per second while the crow is off the ground.
+    pub airborne_stamina_drain: f32,
+    /// Stamina won back per second while the crow stands on a surface.
+    pub stamina_regeneration: f32,
+}
+
+impl Default for CrowFlight {
+    fn default() -> Self {
+        Self {
+            flap_impulse: 400.0,
+            flap_cooldown: 0.25,
+            glide_fall_speed: Some(120.0),
+            glide_speed_boost: 1.5,
+            dive_speed: 600.0,
+            max_speed: 200.0,
+            ground_acceleration: 1600.0,
+            air_acceleration: 800.0,
+            ground_drag: 2000.0,
+            air_drag: 300.0,
+            carry_slowdown: 0.4,
+            flap_stamina: 20.0,
+            airborne_stamina_drain: 8.0,
+            stamina_regeneration: 40.0,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CrowState {
+    Idle,
+    Run,
+    Fly,
+    Pickup,
+    /// Falling slowly with the wings spread, while flap is held on the way down.
+    Glide,
+    /// Swooping down fast until the crow lands, snatches a jewel or flaps.
+    Dive,
+}
+
+/// What the crow is told to do this tick, regardless of where the input comes from.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct CrowControls {
+    pub left: bool,
+    pub right: bool,
+    pub flap: bool,
+    /// Dives when in the air.
+    pub dive: bool,
+    /// How far an analog stick is pushed, from -1 for all the way left to 1 for all the way right.
+    pub stick: f32,
+}
+
+impl CrowControls {
+    /// Which way and how fast the crow should walk, combining the stick with `left` and `right`.
+    pub fn horizontal(&self) -> f32 {
+        (f32::from(u8::from(self.right)) - f32::from(u8::from(self.left)) + self.stick)
+            .clamp(-1.0, 1.0)
+    }
+}
+
+/// Sent once for every flap of the crow's wings.
+pub struct CrowFlapped;
+
+struct CrowSprites {
+    // sprites that are meant to be reused
+    crow_idle: Handle<TextureAtlas>,
+    crow_run: Handle<TextureAtlas>,
+    crow_takeoff: Handle<TextureAtlas>,
+    crow_fly: Handle<TextureAtlas>,
+    crow_pickup: Handle<TextureAtlas>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+enum CrowSystem {
+    Collisions,
+}
+
+/// The player controlled crow: input, animation and collisions with the world.
+pub struct CrowPlugin;
+
+impl Plugin for CrowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrowControls>()
+            .add_event::<CrowFlapped>()
+            .add_startup_system(load_crow_sprites)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(spawn_crow))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(GameplayTick)
+                    .with_system(crow_input.before(PhysicsSystem::Integrate))
+                    .with_system(animate_crow)
+                    .with_system(
+                        collision_check
+                            .label(CrowSystem::Collisions)
+                            .after(PhysicsSystem::Broadphase),
+                    )
+                    .with_system(follow_crow.after(PhysicsSystem::Resolve))
+                    .with_system(carry_jewel.after(CrowSystem::Collisions))
+                    .with_system(flash_crow.after(CrowSystem::Collisions)),
+            )
+            .add_system(keep_crow_in_level);
+    }
+}
+
+fn load_crow_sprites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let idle_handle = asset_server.load("crow.png");
+    let idle_atlas = TextureAtlas::from_grid(idle_handle, Vec2::new(96.0, 96.0), 11, 1);
+    let crow_idle_handle = texture_atlases.add(idle_atlas);
+
+    let run_handle = asset_server.load("crowrun.png");
+    let run_atlas = TextureAtlas::from_grid(run_handle, Vec2::new(96.0, 96.0), 7, 1);
+    let crow_run_handle = texture_atlases.add(run_atlas);
+
+    let takeoff_handle = asset_server.load("crow_takeoff2x.png");
+    let takeoff_atlas = TextureAtlas::from_grid(takeoff_handle, Vec2::new(134.0, 134.0), 6, 1);
+    let crow_takeoff_handle = texture_atlases.add(takeoff_atlas);
+
+    let fly_handle = asset_server.load("crowfly.png");
+    let fly_atlas = TextureAtlas::from_grid(fly_handle, Vec2::new(96.0, 96.0), 9, 1);
+    let crow_fly_handle = texture_atlases.add(fly_atlas);
+
+    let pickup_handle = asset_server.load("crow_pickup2x.png");
+    let pickup_atlas = TextureAtlas::from_grid(pickup_handle, Vec2::new(134.0, 134.0), 2, 1);
+    let crow_pickup_handle = texture_atlases.add(pickup_atlas);
+
+    commands.insert_resource(CrowSprites {
+        crow_idle: crow_idle_handle,
+        crow_run: crow_run_handle,
+        crow_takeoff: crow_takeoff_handle,
+        crow_fly: crow_fly_handle,
+        crow_pickup: crow_pickup_handle,
+    });
+}
+
+fn spawn_crow(
+    mut commands: Commands,
+    sprites: Res<CrowSprites>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+) {
+    let Some(level) = levels.get(&current_level.0) else {
+        return;
+    };
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: sprites.crow_idle.clone(),
+            transform: Transform::from_translation(level.crow_spawn.extend(1.0)),
+            ..Default::default()
+        })
+        .insert(AnimationTimer(Timer::from_seconds(0.1, true)))
+        .insert(KinematicBody::new(CROW_SIZE, 900.0, 600.0))
+        .insert(Contacts::default())
+        .insert(CrowFlight::default())
+        .insert(Health::default())
+        .insert(Stamina::default())
+        .insert(Crow {
+            crow_state: CrowState::Idle,
+            idle_frame_tick_times: vec![10, 1, 1, 1, 1, 1, 1, 1, 2, 10, 10],
+            fly_frame_tick_times: vec![1, 1, 1, 1, 1, 1],
+            glide_frame_tick_times: vec![3, 2, 2, 2, 3, 2, 2, 2, 2],
+            dive_frame_tick_times: vec![0, 0, 0, 0, 0, 0, 0, 0, 0],
+            run_frame_tick_times: vec![1, 1, 1, 1, 1, 1, 1, 1, 1],
+            pickup_frame_tick_times: vec![1, 2],
+            idle_frame_tick_counter: 0,
+            flap_held: false,
+            flap_cooldown: 0.0,
+            carrying: None,
+            score: 0,
+        })
+        .insert(RunEntity);
+}
+
+#[allow(clippy::type_complexity)]
+fn animate_crow(
+    time: Res<Time>,
+    sprites: Res<CrowSprites>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    mut crow_query: Query<(
+        &mut Crow,
+        &mut AnimationTimer,
+        &mut TextureAtlasSprite,
+        &mut Handle<TextureAtlas>,
+    )>,
+) {
+    let Ok((mut crow, mut timer, mut sprite, mut texture_atlas_handle)) =
+        crow_query.get_single_mut()
+    else {
+        return;
+    };
+    timer.0.tick(time.delta());
+    if timer.0.just_finished() {
+        crow.idle_frame_tick_counter += 1;
+        match crow.crow_state {
+            CrowState::Idle => {
+                if crow.idle_frame_tick_counter > crow.idle_frame_tick_times[sprite.index] {
+                    let texture_atlas = texture_atlases.get(&*texture_atlas_handle).unwrap();
+                    sprite.index = (sprite.index + 1) % texture_atlas.textures.len();
+                    crow.idle_frame_tick_counter = 0;
+                }
+            }
+
+            CrowState::Pickup => {
+                if crow.idle_frame_tick_counter > crow.pickup_frame_tick_times[sprite.index] {
+                    crow.idle_frame_tick_counter = 0;
+                    // the pickup plays once, then the crow goes back to standing around
+                    if sprite.index + 1 < crow.pickup_frame_tick_times.len() {
+                        sprite.index += 1;
+                    } else {
+                        crow.crow_state = CrowState::Idle;
+                        *texture_atlas_handle = sprites.crow_idle.clone();
+                        sprite.index = 0;
+                    }
+                }
+            }
+
+            CrowState::Run => {
+                if crow.idle_frame_tick_counter > crow.run_frame_tick_times[sprite.index] {
+                    let texture_atlas = texture_atlases.get(&*texture_atlas_handle).unwrap();
+                    sprite.index = (sprite.index + 1) % texture_atlas.textures.len();
+                    crow.idle_frame_tick_counter = 0;
+                }
+            }
+
+            CrowState::Glide => {
+                if crow.idle_frame_tick_counter > crow.glide_frame_tick_times[sprite.index] {
+                    let texture_atlas = texture_atlases.get(&*texture_atlas_handle).unwrap();
+                    sprite.index = (sprite.index + 1) % texture_atlas.textures.len();
+                    crow.idle_frame_tick_counter = 0;
+                }
+            }
+
+            CrowState::Dive => {
+                if crow.idle_frame_tick_counter > crow.dive_frame_tick_times[sprite.index] {
+                    let texture_atlas = texture_atlases.get(&*texture_atlas_handle).unwrap();
+                    sprite.index = (sprite.index + 1) % texture_atlas.textures.len();
+                    crow.idle_frame_tick_counter = 0;
+                }
+            }
+            _ => {
+                if crow.idle_frame_tick_counter > crow.fly_frame_tick_times[sprite.index] {
+                    let texture_atlas = texture_atlases.get(&*texture_atlas_handle).unwrap();
+                    sprite.index = (sprite.index + 1) % texture_atlas.textures.len();
+                    crow.idle_frame_tick_counter = 0;
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn crow_input(
+    sprites: Res<CrowSprites>,
+    controls: Res<CrowControls>,
+    mut crow_query: Query<(
+        &mut Crow,
+        &CrowFlight,
+        &mut Stamina,
+        &mut KinematicBody,
+        &Contacts,
+        &Health,
+        &Transform,
+        &mut Handle<TextureAtlas>,
+        &mut TextureAtlasSprite,
+    )>,
+    jewel_query: Query<&Jewel>,
+    mut flap_events: EventWriter<CrowFlapped>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+) {
+    let Ok((
+        mut crow,
+        flight,
+        mut stamina,
+        mut body,
+        contacts,
+        health,
+        transform,
+        mut crow_handle,
+        mut sprite,
+    )) = crow_query.get_single_mut()
+    else {
+        return;
+    };
+    let Some(level) = levels.get(&current_level.0) else {
+        return;
+    };
+    let airborne_state = matches!(
+        crow.crow_state,
+        CrowState::Fly | CrowState::Glide | CrowState::Dive
+    );
+    if contacts.grounded && airborne_state {
+        crow.crow_state = CrowState::Idle;
+        *crow_handle = sprites.crow_idle.clone();
+        sprite.index = 0;
+    }
+
+    let burden = crow
+        .carrying
+        .and_then(|jewel| jewel_query.get(jewel).ok())
+        .map_or(1.0, |jewel| 1.0 - jewel.weight * flight.carry_slowdown);
+
+    // perching rests the crow's wings, every moment in the air tires them
+    if contacts.grounded {
+        stamina.current += flight.stamina_regeneration * TIME_STEP;
+    } else {
+        stamina.current -= flight.airborne_stamina_drain * TIME_STEP;
+    }
+    stamina.current = stamina.current.clamp(0.0, stamina.max);
+
+    // holding flap does not keep flapping, every flap takes a fresh press once the cooldown is
+    // over, and presses during the cooldown or without the stamina for a flap are dropped
+    crow.flap_cooldown = (crow.flap_cooldown - TIME_STEP).max(0.0);
+    let flap_pressed = controls.flap && !crow.flap_held;
+    crow.flap_held = controls.flap;
+    if flap_pressed && crow.flap_cooldown == 0.0 && stamina.current >= flight.flap_stamina {
+        crow.flap_cooldown = flight.flap_cooldown;
+        stamina.current -= flight.flap_stamina;
+        body.velocity.y = flight.flap_impulse * burden;
+        flap_events.send(CrowFlapped);
+        if crow.crow_state != CrowState::Fly {
+            crow.crow_state = CrowState::Fly;
+            sprite.index = 0;
+            *crow_handle = sprites.crow_takeoff.clone();
+        }
+    } else if controls.dive
+        && !contacts.grounded
+        && crow.crow_state != CrowState::Dive
+        // a crow still shaken from being caught is not thrown straight back at the person
+        && health.invulnerable == 0.0
+    {
+        crow.crow_state = CrowState::Dive;
+        sprite.index = 0;
+        *crow_handle = sprites.crow_fly.clone();
+    }
+
+    match crow.crow_state {
+        CrowState::Dive => body.velocity.y = -flight.dive_speed,
+        CrowState::Fly | CrowState::Glide => {
+            // keeping flap held on the way down spreads the wings into a glide
+            let gliding = controls.flap && !contacts.grounded && body.velocity.y <= 0.0;
+            match flight.glide_fall_speed {
+                Some(glide_fall_speed) if gliding => {
+                    body.velocity.y = body.velocity.y.max(-glide_fall_speed);
+                    if crow.crow_state == CrowState::Fly {
+                        crow.crow_state = CrowState::Glide;
+                        sprite.index = 0;
+                        *crow_handle = sprites.crow_fly.clone();
+                    }
+                }
+                _ => {
+                    if crow.crow_state == CrowState::Glide {
+                        crow.crow_state = CrowState::Fly;
+                        sprite.index = 0;
+                        *crow_handle = sprites.crow_takeoff.clone();
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    let max_speed = if crow.crow_state == CrowState::Glide {
+        flight.max_speed * flight.glide_speed_boost * burden
+    } else {
+        flight.max_speed * burden
+    };
+
+    let (acceleration, drag) = if contacts.grounded {
+        (flight.ground_acceleration, flight.ground_drag)
+    } else {
+        (flight.air_acceleration, flight.air_drag)
+    };
+    // a stick pushed halfway walks the crow at half its top speed
+    let horizontal = controls.horizontal();
+    if horizontal < 0.0 && !contacts.wall_left && transform.translation.x > level.bounds.min_x {
+        body.velocity.x = (body.velocity.x - acceleration * TIME_STEP).max(max_speed * horizontal);
+        sprite.flip_x = true;
+        if crow.crow_state == CrowState::Idle {
+            crow.crow_state = CrowState::Run;
+            sprite.index = 0;
+            *crow_handle = sprites.crow_run.clone();
+        }
+    } else if horizontal > 0.0
+        && !contacts.wall_right
+        && transform.translation.x < level.bounds.max_x
+    {
+        body.velocity.x = (body.velocity.x + acceleration * TIME_STEP).min(max_speed * horizontal);
+        sprite.flip_x = false;
+        if crow.crow_state == CrowState::Idle {
+            crow.crow_state = CrowState::Run;
+            sprite.index = 0;
+            *crow_handle = sprites.crow_run.clone();
+        }
+    } else {
+        let slowdown = (drag * TIME_STEP).min(body.velocity.x.abs());
+        body.velocity.x -= slowdown * body.velocity.x.signum();
+        if crow.crow_state == CrowState::Run {
+            crow.crow_state = CrowState::Idle;
+            *crow_handle = sprites.crow_idle.clone();
+        }
+    }
+
+    // walls and the edges of the level stop the crow dead
+    if (body.velocity.x < 0.0
+        && (contacts.wall_left || transform.translation.x <= level.bounds.min_x))
+        || (body.velocity.x > 0.0
+            && (contacts.wall_right || transform.translation.x >= level.bounds.max_x))
+    {
+        body.velocity.x = 0.0;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn follow_crow(
+    crow_query: Query<&Transform, With<Crow>>,
+    mut camera_query: Query<&mut Transform, (With<BirdCamera>, Without<Crow>)>,
+    mut background_query: Query<
+        &mut Transform,
+        (With<Background>, Without<BirdCamera>, Without<Crow>),
+    >,
+) {
+    let Ok(transform) = crow_query.get_single() else {
+        return;
+    };
+    if let Ok(mut camera_transform) = camera_query.get_single_mut() {
+        camera_transform.translation = transform.translation;
+    }
+    if let Ok(mut background_transform) = background_query.get_single_mut() {
+        background_transform.translation =
+            Vec3::new(transform.translation.x, transform.translation.y, 0.0);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn collision_check(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    spatial_hash: Res<SpatialHash>,
+    sprites: Res<CrowSprites>,
+    mut crow_query: Query<(
+        &mut Crow,
+        &mut Health,
+        &mut KinematicBody,
+        &Transform,
+        &mut Handle<TextureAtlas>,
+        &mut TextureAtlasSprite,
+    )>,
+    collider_query: Query<(&Collider, &Transform)>,
+    jewel_query: Query<&Jewel>,
+) {
+    let Ok((mut crow, mut health, mut body, crow_transform, mut crow_handle, mut sprite)) =
+        crow_query.get_single_mut()
+    else {
+        return;
+    };
+    health.invulnerable = (health.invulnerable - TIME_STEP).max(0.0);
+    for entity in spatial_hash.query(crow_transform.translation.truncate(), CROW_SIZE) {
+        let Ok((collider, collider_transform)) = collider_query.get(entity) else {
+            continue;
+        };
+        // surfaces are taken care of by the physics, only what the crow picks up or runs into
+        // matters here
+        if collider.collider_type == ColliderType::Surface {
+            continue;
+        }
+        let collision = collide(
+            collider_transform.translation,
+            Vec2::new(collider.width, collider.height),
+            crow_transform.translation,
+            CROW_SIZE,
+        );
+        if collision.is_none() {
+            continue;
+        }
+
+        match collider.collider_type {
+            ColliderType::Jewel => {
+                // one jewel at a time, and none while still shaken from being caught, or the
+                // jewel it just dropped would be picked straight back up
+                if crow.carrying.is_some() || health.invulnerable > 0.0 {
+                    continue;
+                }
+                crow.carrying = Some(entity);
+                commands.entity(entity).remove::<Collider>().insert(Carried);
+                if crow.crow_state == CrowState::Dive {
+                    // a diving crow snatches the jewel without stopping and swoops back up
+                    crow.crow_state = CrowState::Fly;
+                    body.velocity.y = SNATCH_PULL_UP;
+                    *crow_handle = sprites.crow_takeoff.clone();
+                    sprite.index = 0;
+                } else if !matches!(crow.crow_state, CrowState::Fly | CrowState::Glide) {
+                    crow.crow_state = CrowState::Pickup;
+                    crow.idle_frame_tick_counter = 0;
+                    *crow_handle = sprites.crow_pickup.clone();
+                    sprite.index = 0;
+                }
+            }
+            ColliderType::Nest => {
+                if let Some(jewel) = crow.carrying.take() {
+                    crow.score += jewel_query.get(jewel).map_or(0, |jewel| jewel.value);
+                    commands.entity(jewel).despawn();
+                }
+            }
+            ColliderType::Person => {
+                // several people can touch the crow in the same tick, only the first one counts
+                if health.invulnerable > 0.0 || health.lives == 0 {
+                    continue;
+                }
+                health.lives -= 1;
+                health.invulnerable = INVULNERABILITY_TIME;
+                let away = if crow_transform.translation.x < collider_transform.translation.x {
+                    -1.0
+                } else {
+                    1.0
+                };
+                body.velocity = Vec2::new(KNOCKBACK.x * away, KNOCKBACK.y);
+                if crow.crow_state == CrowState::Dive {
+                    // the dive would drive the crow straight back down into the person
+                    crow.crow_state = CrowState::Fly;
+                    *crow_handle = sprites.crow_takeoff.clone();
+                    sprite.index = 0;
+                }
+
+                // whatever the crow was carrying falls where it was caught
+                if let Some(jewel) = crow.carrying.take() {
+                    let size = jewel_query
+                        .get(jewel)
+                        .map_or(Vec2::new(64.0, 64.0), |jewel| jewel.kind.size());
+                    let feet = crow_transform.translation.y - CROW_SIZE.y / 2.0;
+                    commands
+                        .entity(jewel)
+                        .remove::<Carried>()
+                        .insert(Transform::from_xyz(
+                            crow_transform.translation.x,
+                            feet + size.y / 2.0,
+                            1.0,
+                        ))
+                        .insert(Collider {
+                            width: size.x,
+                            height: size.y,
+                            collider_type: ColliderType::Jewel,
+                        });
+                }
+
+                if health.lives == 0 {
+                    let _ = state.set(GameState::GameOver);
+                }
+            }
+            ColliderType::Surface => {}
+        }
+    }
+}
+
+// The crow flashes while it cannot be caught.
+fn flash_crow(mut crow_query: Query<(&Health, &mut TextureAtlasSprite), With<Crow>>) {
+    let Ok((health, mut sprite)) = crow_query.get_single_mut() else {
+        return;
+    };
+    let flashes = (health.invulnerable / FLASH_INTERVAL).floor() as u32;
+    let hidden = health.invulnerable > 0.0 && flashes.is_multiple_of(2);
+    sprite.color.set_a(if hidden { 0.2 } else { 1.0 });
+}
+
+// The carried jewel dangles from the crow's beak.
+fn carry_jewel(
+    crow_query: Query<(&Crow, &Transform, &TextureAtlasSprite)>,
+    mut jewel_query: Query<&mut Transform, (With<Carried>, Without<Crow>)>,
+) {
+    let Ok((crow, crow_transform, sprite)) = crow_query.get_single() else {
+        return;
+    };
+    let Some(mut transform) = crow
+        .carrying
+        .and_then(|jewel| jewel_query.get_mut(jewel).ok())
+    else {
+        return;
+    };
+    let facing = if sprite.flip_x { -1.0 } else { 1.0 };
+    transform.translation = crow_transform.translation + Vec3::new(20.0 * facing, -15.0, 0.1);
+    transform.scale = Vec3::splat(0.5);
+}
+
+// After a level file is edited the crow stays where it is, unless it would now be stuck inside a
+// surface or outside the level's bounds.
+fn keep_crow_in_level(
+    mut level_events: EventReader<AssetEvent<Level>>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    mut crow_query: Query<(&mut KinematicBody, &mut Transform), With<Crow>>,
+) {
+    for event in level_events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        let (Some(level), Ok((mut body, mut transform))) =
+            (levels.get(handle), crow_query.get_single_mut())
+        else {
+            continue;
+        };
+        if *handle != current_level.0 {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let out_of_bounds = position.x < level.bounds.min_x || position.x > level.bounds.max_x;
+        let stuck = level.surfaces.iter().any(|surface| {
+            let distance = (surface.position - position).abs();
+            // collisions leave the crow touching whatever it rests against, not inside it
+            let reach = (surface.size + CROW_SIZE) / 2.0 - Vec2::splat(CONTACT_SKIN);
+            distance.x < reach.x && distance.y < reach.y
+        });
+        if out_of_bounds || stuck {
+            transform.translation = level.crow_spawn.extend(transform.translation.z);
+            body.velocity = Vec2::ZERO;
+        }
+    }
+}
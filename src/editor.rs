@@ -0,0 +1,381 @@
+use std::path::Path;
+
+use bevy::asset::AssetServerSettings;
+use bevy::prelude::*;
+
+use crate::jewel::{spawn_jewel, JewelKind};
+use crate::level::{CurrentLevel, Level, PersonSpeeds, Surface};
+use crate::people::{spawn_person, PeopleSprites, Person};
+use crate::state::GameState;
+use crate::world::{
+    spawn_nest, spawn_surface, BirdCamera, Collider, ColliderType, LevelObject, SurfaceTexture,
+};
+
+// everything placed in the editor lines up on this grid
+const GRID: f32 = 16.0;
+
+const CAMERA_SPEED: f32 = 600.0;
+
+/// The kind of collider a left click on empty space places.
+pub struct EditorBrush(pub ColliderType);
+
+/// Where the mouse cursor points at in the world, or `None` while it is outside the window. It
+/// follows the primary window's cursor through the bird camera, without a window it only
+/// changes when it is written to directly.
+#[derive(Default)]
+pub struct EditorCursor(pub Option<Vec2>);
+
+#[derive(Component)]
+struct EditorUI;
+
+enum Drag {
+    Move { entity: Entity, offset: Vec2 },
+    // the corner opposite to the one being dragged stays where it is
+    Resize { entity: Entity, anchor: Vec2 },
+}
+
+/// Edit the level over the running world, toggled with [Tab] while playing.
+///
+/// [1] [2] [3] [4] pick a surface, jewel, person or nest brush. Left click places the brush on
+/// empty space or drags a collider around, shift + left drag resizes a surface and right click
+/// deletes a collider. The arrow keys move the camera and [Ctrl] + [S] writes the level back to its file. Saving
+/// writes out what the level placed and what was added in the editor, see `LevelObject`, and
+/// leaves out the jewels and people a run spawned on its own.
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EditorBrush(ColliderType::Surface))
+            .init_resource::<EditorCursor>()
+            .add_system_to_stage(CoreStage::PreUpdate, track_cursor)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(toggle_editor))
+            .add_system_set(
+                SystemSet::on_update(GameState::Editor)
+                    .with_system(toggle_editor)
+                    .with_system(pick_brush)
+                    .with_system(edit_colliders)
+                    .with_system(move_camera)
+                    .with_system(save_level),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Editor).with_system(spawn_editor_ui))
+            .add_system_set(SystemSet::on_exit(GameState::Editor).with_system(despawn_editor_ui));
+    }
+}
+
+fn toggle_editor(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        let result = match state.current() {
+            GameState::Editor => state.pop(),
+            _ => state.push(GameState::Editor),
+        };
+        if result.is_ok() {
+            keyboard_input.reset(KeyCode::Tab);
+        }
+    }
+}
+
+fn spawn_editor_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::FlexEnd,
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(5.0),
+                    left: Val::Px(15.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "EDITOR  [1] Surface [2] Jewel [3] Person [4] Nest  [Shift] Resize  [Ctrl+S] Save"
+                    .to_string(),
+                TextStyle {
+                    font: asset_server.load("Inconsolata-Regular.ttf"),
+                    font_size: 20.0,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(EditorUI);
+}
+
+fn despawn_editor_ui(mut commands: Commands, ui_query: Query<Entity, With<EditorUI>>) {
+    for entity in ui_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn pick_brush(keyboard_input: Res<Input<KeyCode>>, mut brush: ResMut<EditorBrush>) {
+    if keyboard_input.just_pressed(KeyCode::Key1) {
+        brush.0 = ColliderType::Surface;
+    } else if keyboard_input.just_pressed(KeyCode::Key2) {
+        brush.0 = ColliderType::Jewel;
+    } else if keyboard_input.just_pressed(KeyCode::Key3) {
+        brush.0 = ColliderType::Person;
+    } else if keyboard_input.just_pressed(KeyCode::Key4) {
+        brush.0 = ColliderType::Nest;
+    }
+}
+
+fn move_camera(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut camera_query: Query<&mut Transform, With<BirdCamera>>,
+) {
+    let mut direction = Vec3::ZERO;
+    if keyboard_input.pressed(KeyCode::Left) {
+        direction.x -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::Right) {
+        direction.x += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::Up) {
+        direction.y += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::Down) {
+        direction.y -= 1.0;
+    }
+    for mut transform in camera_query.iter_mut() {
+        transform.translation += direction * CAMERA_SPEED * time.delta_seconds();
+    }
+}
+
+fn snap(value: Vec2) -> Vec2 {
+    (value / GRID).round() * GRID
+}
+
+fn track_cursor(
+    windows: Option<Res<Windows>>,
+    camera_query: Query<&Transform, With<BirdCamera>>,
+    mut cursor: ResMut<EditorCursor>,
+) {
+    if let Some(windows) = windows {
+        cursor.0 = cursor_position(&windows, &camera_query);
+    }
+}
+
+// Where the cursor points at in the world, seen through the bird camera.
+fn cursor_position(
+    windows: &Windows,
+    camera_query: &Query<&Transform, With<BirdCamera>>,
+) -> Option<Vec2> {
+    let window = windows.get_primary()?;
+    let cursor = window.cursor_position()?;
+    let camera = camera_query.get_single().ok()?;
+    let window_size = Vec2::new(window.width(), window.height());
+    Some(cursor - window_size / 2.0 + camera.translation.truncate())
+}
+
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn edit_colliders(
+    mut commands: Commands,
+    mut drag: Local<Option<Drag>>,
+    cursor: Res<EditorCursor>,
+    mouse_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    brush: Res<EditorBrush>,
+    asset_server: Res<AssetServer>,
+    people_sprites: Res<PeopleSprites>,
+    mut collider_query: Query<(
+        Entity,
+        &mut Collider,
+        &mut Transform,
+        Option<&mut Sprite>,
+        Option<&mut LevelObject>,
+        Option<&mut Person>,
+    )>,
+) {
+    let Some(cursor) = cursor.0 else {
+        return;
+    };
+
+    if mouse_input.just_released(MouseButton::Left) {
+        *drag = None;
+    }
+
+    // the smallest collider under the cursor wins, so bricks can be picked off the floor
+    let mut hovered = None;
+    let mut hovered_area = f32::INFINITY;
+    for (entity, collider, transform, _, _, _) in collider_query.iter_mut() {
+        let distance = (transform.translation.truncate() - cursor).abs();
+        let area = collider.width * collider.height;
+        if distance.x < collider.width / 2.0
+            && distance.y < collider.height / 2.0
+            && area < hovered_area
+        {
+            hovered = Some(entity);
+            hovered_area = area;
+        }
+    }
+
+    if mouse_input.just_pressed(MouseButton::Right) {
+        if let Some(entity) = hovered {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    if mouse_input.just_pressed(MouseButton::Left) {
+        let resizing =
+            keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift);
+        match hovered {
+            Some(entity) => {
+                let (_, collider, transform, _, _, _) = collider_query.get_mut(entity).unwrap();
+                let center = transform.translation.truncate();
+                *drag = if !resizing {
+                    Some(Drag::Move {
+                        entity,
+                        offset: center - cursor,
+                    })
+                } else if collider.collider_type == ColliderType::Surface {
+                    let half_size = Vec2::new(collider.width, collider.height) / 2.0;
+                    let anchor = center - half_size * (cursor - center).signum();
+                    Some(Drag::Resize { entity, anchor })
+                } else {
+                    // jewels, people and nests always have the size they are drawn at
+                    None
+                };
+            }
+            None => {
+                let position = snap(cursor);
+                match brush.0 {
+                    ColliderType::Surface => {
+                        let surface = Surface {
+                            texture: "brick.png".to_string(),
+                            position,
+                            size: Vec2::new(GRID * 4.0, GRID * 4.0),
+                        };
+                        spawn_surface(&mut commands, &asset_server, &surface);
+                    }
+                    ColliderType::Jewel => {
+                        let jewel =
+                            spawn_jewel(&mut commands, &asset_server, JewelKind::Ring, position);
+                        commands.entity(jewel).insert(LevelObject {
+                            collider_type: ColliderType::Jewel,
+                            position,
+                        });
+                    }
+                    ColliderType::Person => {
+                        let person = Person::new(position, Vec::new(), PersonSpeeds::default());
+                        let person = spawn_person(&mut commands, &people_sprites, position, person);
+                        commands.entity(person).insert(LevelObject {
+                            collider_type: ColliderType::Person,
+                            position,
+                        });
+                    }
+                    ColliderType::Nest => spawn_nest(&mut commands, position),
+                }
+            }
+        }
+        return;
+    }
+
+    match *drag {
+        Some(Drag::Move { entity, offset }) => {
+            if let Ok((_, _, mut transform, _, level_object, person)) =
+                collider_query.get_mut(entity)
+            {
+                let position = snap(cursor + offset);
+                if let Some(mut person) = person {
+                    person.move_by(position - transform.translation.truncate());
+                }
+                transform.translation.x = position.x;
+                transform.translation.y = position.y;
+                if let Some(mut level_object) = level_object {
+                    level_object.position = position;
+                }
+            }
+        }
+        Some(Drag::Resize { entity, anchor }) => {
+            if let Ok((_, mut collider, mut transform, sprite, level_object, _)) =
+                collider_query.get_mut(entity)
+            {
+                let corner = snap(cursor);
+                let size = (corner - anchor).abs().max(Vec2::splat(GRID));
+                let center = anchor + size / 2.0 * (corner - anchor).signum();
+                collider.width = size.x;
+                collider.height = size.y;
+                transform.translation.x = center.x;
+                transform.translation.y = center.y;
+                if let Some(mut level_object) = level_object {
+                    level_object.position = center;
+                }
+                if let Some(mut sprite) = sprite {
+                    sprite.custom_size = Some(size);
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn save_level(
+    keyboard_input: Res<Input<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    asset_settings: Option<Res<AssetServerSettings>>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    object_query: Query<(&LevelObject, Option<&Collider>, Option<&SurfaceTexture>)>,
+) {
+    let ctrl =
+        keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl);
+    if !(ctrl && keyboard_input.just_pressed(KeyCode::S)) {
+        return;
+    }
+    let Some(mut level) = levels.get(&current_level.0).cloned() else {
+        return;
+    };
+    level.surfaces.clear();
+    level.jewels.clear();
+    level.nests.clear();
+    // the people placed at random are left to `PeopleSpawns::count`
+    level.people.positions.clear();
+    for (object, collider, texture) in object_query.iter() {
+        let position = object.position;
+        match object.collider_type {
+            ColliderType::Surface => {
+                let Some(collider) = collider else {
+                    continue;
+                };
+                level.surfaces.push(Surface {
+                    texture: texture.map_or("brick.png".to_string(), |texture| texture.0.clone()),
+                    position,
+                    size: Vec2::new(collider.width, collider.height),
+                });
+            }
+            // every jewel the level places or the editor adds is a ring
+            ColliderType::Jewel => level.jewels.push(position),
+            ColliderType::Person => level.people.positions.push(position),
+            ColliderType::Nest => level.nests.push(position),
+        }
+    }
+
+    let Some(path) = asset_server.get_handle_path(&current_level.0) else {
+        warn!("the current level was not loaded from a file and cannot be saved");
+        return;
+    };
+    let asset_folder = asset_settings.map_or("assets".to_string(), |settings| {
+        settings.asset_folder.clone()
+    });
+    match write_level(&level, &Path::new(&asset_folder).join(path.path())) {
+        Ok(()) => info!("saved level to {:?}", path.path()),
+        Err(err) => error!("could not save level to {:?}: {}", path.path(), err),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_level(level: &Level, path: &Path) -> anyhow::Result<()> {
+    let full_path = bevy::asset::FileAssetIo::get_root_path().join(path);
+    let ron = ron::ser::to_string_pretty(level, ron::ser::PrettyConfig::new())?;
+    std::fs::write(full_path, ron)?;
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_level(_level: &Level, _path: &Path) -> anyhow::Result<()> {
+    anyhow::bail!("levels cannot be saved in the browser")
+}
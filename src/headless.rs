@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+
+use bevy::app::{PluginGroupBuilder, ScheduleRunnerPlugin};
+use bevy::asset::AssetPlugin;
+use bevy::core::CorePlugin;
+use bevy::input::gamepad::gamepad_connection_system;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use crate::crow::{CrowControls, CrowPlugin};
+use crate::highscore::HighScorePlugin;
+use crate::hud::HudPlugin;
+use crate::input::{ActionsPlugin, PlayerControlsSystem};
+use crate::jewel::JewelPlugin;
+use crate::level::LevelPlugin;
+use crate::menu::MenuPlugin;
+use crate::noise::NoisePlugin;
+use crate::people::PeoplePlugin;
+use crate::physics::PhysicsPlugin;
+use crate::state::GameStatePlugin;
+use crate::world::WorldPlugin;
+
+/// The gameplay without a window, renderer or audio, for simulations and tests.
+///
+/// Every `App::update` is one gameplay tick and the crow is driven by the `ScriptedInput`
+/// resource, add `PlayerInputPlugin` to play it from the keyboard or a gamepad instead. The
+/// player's actions, which drive the menus, come from an `Input<KeyCode>`,
+/// `Input<GamepadButton>` and `Axis<GamepadAxis>` that only change when they are written to
+/// directly. Gamepads are connected by sending a `GamepadEvent` and typing is done by sending
+/// `ReceivedCharacter` events. High scores and bindings are kept in memory and never touch the
+/// disk.
+pub struct HeadlessPlugins;
+
+impl PluginGroup for HeadlessPlugins {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        group
+            .add(CorePlugin)
+            .add(ScheduleRunnerPlugin)
+            .add(AssetPlugin)
+            .add(HeadlessResourcesPlugin)
+            .add(GameStatePlugin {
+                fixed_timestep: false,
+            })
+            .add(LevelPlugin)
+            .add(WorldPlugin)
+            .add(PhysicsPlugin)
+            .add(CrowPlugin)
+            .add(PeoplePlugin)
+            .add(NoisePlugin)
+            .add(JewelPlugin)
+            .add(HighScorePlugin { path: None })
+            .add(HudPlugin)
+            .add(MenuPlugin)
+            .add(ActionsPlugin { path: None })
+            .add(ScriptedInputPlugin);
+    }
+}
+
+// Resources the gameplay expects that are normally added by `DefaultPlugins`.
+struct HeadlessResourcesPlugin;
+
+impl Plugin for HeadlessResourcesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Image>()
+            .add_asset::<TextureAtlas>()
+            .add_asset::<Font>()
+            .add_event::<ReceivedCharacter>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .init_resource::<Gamepads>()
+            .add_event::<GamepadEvent>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                gamepad_connection_system.label(InputSystem),
+            );
+    }
+}
+
+/// A queue of controls for the crow, each held for a number of gameplay ticks.
+#[derive(Default)]
+pub struct ScriptedInput {
+    steps: VecDeque<(CrowControls, u32)>,
+}
+
+impl ScriptedInput {
+    pub fn hold(&mut self, controls: CrowControls, ticks: u32) -> &mut Self {
+        if ticks > 0 {
+            self.steps.push_back((controls, ticks));
+        }
+        self
+    }
+
+    pub fn wait(&mut self, ticks: u32) -> &mut Self {
+        self.hold(CrowControls::default(), ticks)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+struct ScriptedInputPlugin;
+
+impl Plugin for ScriptedInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScriptedInput>().add_system_to_stage(
+            CoreStage::PreUpdate,
+            play_script.before(PlayerControlsSystem),
+        );
+    }
+}
+
+fn play_script(mut script: ResMut<ScriptedInput>, mut controls: ResMut<CrowControls>) {
+    *controls = match script.steps.front_mut() {
+        Some((step, ticks)) => {
+            let step = *step;
+            *ticks -= 1;
+            if *ticks == 0 {
+                script.steps.pop_front();
+            }
+            step
+        }
+        None => CrowControls::default(),
+    };
+}
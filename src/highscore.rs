@@ -0,0 +1,246 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::crow::Crow;
+use crate::input::{Action, Actions};
+use crate::level::{level_name, CurrentLevel};
+use crate::state::{GameState, GameplayTick, TIME_STEP};
+
+/// How many runs the high score table keeps.
+pub const MAX_HIGH_SCORES: usize = 10;
+/// The longest name that can be entered for a high score.
+pub const MAX_NAME_LENGTH: usize = 12;
+// the name a high score gets when it is confirmed without typing one
+const DEFAULT_NAME: &str = "Crow";
+
+/// One finished run in the high score table.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: usize,
+    /// Seconds the run lasted.
+    pub duration: f32,
+    pub level: String,
+    /// Seconds since the Unix epoch when the run ended.
+    pub date: u64,
+}
+
+/// The best runs so far, highest score first.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    /// Reads the table from `path`, a missing file is an empty table.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => ron::de::from_str(&text)
+                .with_context(|| format!("could not parse high scores in {}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err)
+                .with_context(|| format!("could not read high scores from {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("could not create {}", dir.display()))?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, text)
+            .with_context(|| format!("could not write high scores to {}", path.display()))
+    }
+
+    /// Whether a run with `score` would make it into the table.
+    pub fn qualifies(&self, score: usize) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Adds `entry` in its place and returns that place, or `None` if it did not make the table.
+    /// Ties go to the run that set the score first.
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+/// Where the high score table lives on disk, `None` keeps it in memory only.
+pub struct HighScoreFile(pub Option<PathBuf>);
+
+/// A run that made it into the table and is waiting for its name on the game over screen.
+#[derive(Default)]
+pub struct PendingHighScore(pub Option<HighScoreEntry>);
+
+/// Seconds the current run has been going.
+#[derive(Default)]
+pub struct RunTime(pub f32);
+
+/// `$XDG_DATA_HOME/crow_jewels/highscores.ron`, falling back to `~/.local/share`.
+pub fn default_high_score_path() -> Option<PathBuf> {
+    user_file("XDG_DATA_HOME", ".local/share", "highscores.ron")
+}
+
+/// `file` in the game's folder under the XDG base directory in `env_var`, or under `fallback`
+/// in the home directory if it is not set.
+pub(crate) fn user_file(env_var: &str, fallback: &str, file: &str) -> Option<PathBuf> {
+    let base = std::env::var_os(env_var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(fallback)))?;
+    Some(base.join("crow_jewels").join(file))
+}
+
+/// Formats seconds since the Unix epoch as a `YYYY-MM-DD` date.
+pub fn format_date(date: u64) -> String {
+    // days to civil date, from Howard Hinnant's date algorithms
+    let days = (date / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Keeps the high score table, times runs and records the ones worth keeping when they end.
+pub struct HighScorePlugin {
+    /// Where to keep the table, `None` keeps it in memory for this session only.
+    pub path: Option<PathBuf>,
+}
+
+impl Default for HighScorePlugin {
+    fn default() -> Self {
+        Self {
+            path: default_high_score_path(),
+        }
+    }
+}
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        let high_scores = match &self.path {
+            Some(path) => HighScores::load(path).unwrap_or_else(|err| {
+                warn!("starting with an empty high score table: {:?}", err);
+                HighScores::default()
+            }),
+            None => HighScores::default(),
+        };
+        app.insert_resource(high_scores)
+            .insert_resource(HighScoreFile(self.path.clone()))
+            .init_resource::<PendingHighScore>()
+            .init_resource::<RunTime>()
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(reset_run_time))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(GameplayTick)
+                    .with_system(tick_run_time),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(check_high_score))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(enter_name));
+    }
+}
+
+fn reset_run_time(mut run_time: ResMut<RunTime>, mut pending: ResMut<PendingHighScore>) {
+    run_time.0 = 0.0;
+    pending.0 = None;
+}
+
+fn tick_run_time(mut run_time: ResMut<RunTime>) {
+    run_time.0 += TIME_STEP;
+}
+
+// Holds on to a run that made the table until the player has typed a name for it.
+fn check_high_score(
+    crow_query: Query<&Crow>,
+    high_scores: Res<HighScores>,
+    run_time: Res<RunTime>,
+    current_level: Res<CurrentLevel>,
+    asset_server: Res<AssetServer>,
+    mut pending: ResMut<PendingHighScore>,
+) {
+    let Ok(crow) = crow_query.get_single() else {
+        return;
+    };
+    if !high_scores.qualifies(crow.score) {
+        return;
+    }
+    let level = asset_server
+        .get_handle_path(&current_level.0)
+        .map(|path| level_name(&path.path().to_string_lossy()).to_string())
+        .unwrap_or_default();
+    let date = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    pending.0 = Some(HighScoreEntry {
+        name: String::new(),
+        score: crow.score,
+        duration: run_time.0,
+        level,
+        date,
+    });
+}
+
+fn enter_name(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut actions: ResMut<Actions>,
+    mut pending: ResMut<PendingHighScore>,
+    mut high_scores: ResMut<HighScores>,
+    file: Res<HighScoreFile>,
+) {
+    let Some(entry) = &mut pending.0 else {
+        characters.iter().for_each(drop);
+        return;
+    };
+    for event in characters.iter() {
+        if !event.char.is_control() && entry.name.chars().count() < MAX_NAME_LENGTH {
+            entry.name.push(event.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        entry.name.pop();
+    }
+    if !actions.just_pressed(Action::Interact) {
+        return;
+    }
+    // the same press must not restart the run straight away
+    actions.reset_all();
+    let mut entry = pending.0.take().unwrap();
+    entry.name = entry.name.trim().to_string();
+    // there is no typing on a gamepad, confirming from one saves the run under a stand-in name
+    if entry.name.is_empty() {
+        entry.name = DEFAULT_NAME.to_string();
+    }
+    high_scores.insert(entry);
+    if let Some(path) = &file.0 {
+        if let Err(err) = high_scores.save(path) {
+            error!("could not save the high score table: {:?}", err);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+
+use crate::crow::{Crow, Health, Stamina};
+use crate::highscore::{format_date, HighScores, PendingHighScore};
+use crate::input::{Action, Actions, Bindings};
+use crate::noise::NoiseLevel;
+use crate::state::{GameState, GameplayTick};
+use crate::world::RunEntity;
+
+#[derive(Component)]
+pub struct GameOverUI;
+
+#[derive(Component)]
+pub struct ScoreText;
+
+/// The game over message, the name prompt for a new high score and the high score table.
+#[derive(Component)]
+pub struct GameOverText;
+
+#[derive(Component)]
+pub struct LivesText;
+
+/// The filled part of the noise meter, as wide as the crow has been loud.
+#[derive(Component)]
+pub struct NoiseMeter;
+
+/// The filled part of the stamina bar, as wide as the crow has stamina left.
+#[derive(Component)]
+pub struct StaminaMeter;
+
+/// Score and lives display, the noise and stamina meters, the title text and the game over screen.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Playing).with_system(spawn_hud))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(GameplayTick)
+                    .with_system(ui),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(spawn_gameover_ui))
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver)
+                    .with_system(gameover_screen)
+                    .with_system(gameover_text),
+            );
+    }
+}
+
+fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("Inconsolata-Regular.ttf");
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::FlexEnd,
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(5.0),
+                    left: Val::Px(15.0),
+                    ..Default::default()
+                },
+                size: Size {
+                    width: Val::Px(200.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "Score: 0".to_string(),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 50.0,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(ScoreText)
+        .insert(RunEntity);
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::FlexEnd,
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(5.0),
+                    right: Val::Px(15.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "Lives: 3".to_string(),
+                TextStyle {
+                    font,
+                    font_size: 50.0,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(LivesText)
+        .insert(RunEntity);
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(65.0),
+                    left: Val::Px(15.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Px(200.0), Val::Px(16.0)),
+                ..Default::default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.3).into(),
+            ..Default::default()
+        })
+        .insert(RunEntity)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                        ..Default::default()
+                    },
+                    color: Color::rgb(0.9, 0.2, 0.1).into(),
+                    ..Default::default()
+                })
+                .insert(NoiseMeter);
+        });
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(87.0),
+                    left: Val::Px(15.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Px(200.0), Val::Px(16.0)),
+                ..Default::default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.3).into(),
+            ..Default::default()
+        })
+        .insert(RunEntity)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        ..Default::default()
+                    },
+                    color: Color::rgb(0.2, 0.7, 0.3).into(),
+                    ..Default::default()
+                })
+                .insert(StaminaMeter);
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn ui(
+    mut score_query: Query<(&mut Text, With<ScoreText>)>,
+    mut lives_query: Query<&mut Text, (With<LivesText>, Without<ScoreText>)>,
+    mut noise_query: Query<&mut Style, With<NoiseMeter>>,
+    mut stamina_query: Query<&mut Style, (With<StaminaMeter>, Without<NoiseMeter>)>,
+    crow_query: Query<(&Crow, &Health, &Stamina)>,
+    noise_level: Res<NoiseLevel>,
+) {
+    if let Ok(mut style) = noise_query.get_single_mut() {
+        style.size.width = Val::Percent(noise_level.0 * 100.0);
+    }
+    let Ok((crow, health, stamina)) = crow_query.get_single() else {
+        return;
+    };
+    if let Ok(mut style) = stamina_query.get_single_mut() {
+        style.size.width = Val::Percent(stamina.current / stamina.max * 100.0);
+    }
+    if let Ok((mut score, _)) = score_query.get_single_mut() {
+        score.sections[0].value = format!("Score: {}", crow.score);
+    }
+    if let Ok(mut lives) = lives_query.get_single_mut() {
+        lives.sections[0].value = format!("Lives: {}", health.lives);
+    }
+}
+
+fn spawn_gameover_ui(
+    mut commands: Commands,
+    score_query: Query<Entity, With<ScoreText>>,
+    asset_server: Res<AssetServer>,
+) {
+    for score_entity in score_query.iter() {
+        commands.entity(score_entity).despawn();
+    }
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::SpaceBetween,
+                ..Default::default()
+            },
+            color: Color::RED.into(),
+            ..Default::default()
+        })
+        .insert(GameOverUI {})
+        .insert(RunEntity);
+    let font = asset_server.load("Inconsolata-Regular.ttf");
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            text: Text::with_section(
+                String::new(),
+                TextStyle {
+                    font,
+                    font_size: 30.0,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(GameOverUI {})
+        .insert(GameOverText)
+        .insert(RunEntity);
+}
+
+/// Lays out the high score table, one run per line.
+pub fn high_score_table(high_scores: &HighScores) -> String {
+    let mut table = String::from("High scores\n");
+    for (rank, entry) in high_scores.entries.iter().enumerate() {
+        table.push_str(&format!(
+            "{:>2}. {:<12} {:>5} {:>4}s {:<8} {}\n",
+            rank + 1,
+            entry.name,
+            entry.score,
+            entry.duration.round(),
+            entry.level,
+            format_date(entry.date)
+        ));
+    }
+    table
+}
+
+fn gameover_text(
+    mut text_query: Query<&mut Text, With<GameOverText>>,
+    pending: Res<PendingHighScore>,
+    high_scores: Res<HighScores>,
+    bindings: Res<Bindings>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+    let value = match &pending.0 {
+        Some(entry) => format!(
+            "        Game Over\n  New high score: {}!\n  Your name: {}_\n  Press {} to save",
+            entry.score,
+            entry.name,
+            bindings.describe(Action::Interact)
+        ),
+        None => format!(
+            "        Game Over\n  Press {} to restart\n\n{}",
+            bindings.describe(Action::Flap),
+            high_score_table(&high_scores)
+        ),
+    };
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
+
+fn gameover_screen(
+    actions: Res<Actions>,
+    pending: Res<PendingHighScore>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let restart = actions.just_pressed(Action::Flap) || actions.just_pressed(Action::Interact);
+    // a space in the name being typed is not a restart
+    if pending.0.is_none() && restart {
+        let _ = state.set(GameState::Playing);
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::crow::CrowControls;
+use crate::highscore::user_file;
+
+// how far down the left stick has to be pushed to dive
+const STICK_DIVE: f32 = 0.5;
+
+/// Something the player can do, whichever key or button it is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Flap,
+    /// Picks a menu item, confirms a name and restarts after a game over.
+    Interact,
+    /// Leaves a menu page.
+    Back,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Flap,
+        Action::Interact,
+        Action::Back,
+        Action::Pause,
+    ];
+}
+
+/// The keys and gamepad buttons that trigger one action.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Binding {
+    #[serde(default)]
+    pub keys: Vec<KeyCode>,
+    #[serde(default)]
+    pub buttons: Vec<GamepadButtonType>,
+}
+
+impl Binding {
+    fn new(keys: &[KeyCode], buttons: &[GamepadButtonType]) -> Self {
+        Self {
+            keys: keys.to_vec(),
+            buttons: buttons.to_vec(),
+        }
+    }
+}
+
+/// What every action is bound to, as kept in the bindings file. Actions may share keys and
+/// buttons, and the controls page lets the player bind them that way on purpose: flying reads
+/// `Flap` and `Pause` while the menus read `Interact` and `Back`, and the screens that read both
+/// treat a shared press as one.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Bindings {
+    pub actions: BTreeMap<Action, Binding>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use GamepadButtonType::*;
+        let actions = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let binding = match action {
+                    Action::MoveLeft => Binding::new(&[KeyCode::Left], &[DPadLeft]),
+                    Action::MoveRight => Binding::new(&[KeyCode::Right], &[DPadRight]),
+                    Action::MoveUp => Binding::new(&[KeyCode::Up], &[DPadUp]),
+                    Action::MoveDown => Binding::new(&[KeyCode::Down], &[DPadDown]),
+                    // a gamepad's South button flaps while flying and picks in the menus, and
+                    // Escape pauses the game and leaves menu pages
+                    Action::Flap => Binding::new(&[KeyCode::Space], &[South]),
+                    Action::Interact => Binding::new(&[KeyCode::Return], &[South]),
+                    Action::Back => Binding::new(&[KeyCode::Escape], &[East]),
+                    Action::Pause => Binding::new(&[KeyCode::Escape], &[Start]),
+                };
+                (action, binding)
+            })
+            .collect();
+        Self { actions }
+    }
+}
+
+impl Bindings {
+    /// Reads the bindings from `path`. A missing file gives the default bindings, and so does
+    /// every action the file leaves out.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut bindings = Self::default();
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                let loaded: Self = ron::de::from_str(&text)
+                    .with_context(|| format!("could not parse bindings in {}", path.display()))?;
+                bindings.actions.extend(loaded.actions);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("could not read bindings from {}", path.display()))
+            }
+        }
+        Ok(bindings)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("could not create {}", dir.display()))?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, text)
+            .with_context(|| format!("could not write bindings to {}", path.display()))
+    }
+
+    pub fn get(&self, action: Action) -> Option<&Binding> {
+        self.actions.get(&action)
+    }
+
+    /// The keys and buttons bound to `action` as shown to the player, such as `Space / (South)`.
+    pub fn describe(&self, action: Action) -> String {
+        let Some(binding) = self.get(action) else {
+            return "unbound".to_string();
+        };
+        let keys = binding.keys.iter().map(|key| format!("{:?}", key));
+        let buttons = binding
+            .buttons
+            .iter()
+            .map(|button| format!("({:?})", button));
+        let names: Vec<String> = keys.chain(buttons).collect();
+        if names.is_empty() {
+            "unbound".to_string()
+        } else {
+            names.join(" / ")
+        }
+    }
+}
+
+/// Where the bindings are kept on disk, `None` keeps changes in memory only.
+pub struct BindingsFile(pub Option<PathBuf>);
+
+/// `$XDG_CONFIG_HOME/crow_jewels/bindings.ron`, falling back to `~/.config`.
+pub fn default_bindings_path() -> Option<PathBuf> {
+    user_file("XDG_CONFIG_HOME", ".config", "bindings.ron")
+}
+
+/// Which actions are held and which were started this frame, from every bound key and button.
+#[derive(Debug, Default)]
+pub struct Actions {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl Actions {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Forgets everything for the rest of the frame, so a press that switched the game to another
+    /// state is not seen again by the systems of that state.
+    pub fn reset_all(&mut self) {
+        self.pressed.clear();
+        self.just_pressed.clear();
+    }
+}
+
+/// `Actions` are brought up to date with the keyboard and gamepads in this system.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct ActionsSystem;
+
+/// Resolves the player's actions from the keyboard and gamepad bindings in a bindings file.
+pub struct ActionsPlugin {
+    /// Where to keep the bindings, `None` keeps changes to them in memory for this session only.
+    pub path: Option<PathBuf>,
+}
+
+impl Default for ActionsPlugin {
+    fn default() -> Self {
+        Self {
+            path: default_bindings_path(),
+        }
+    }
+}
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        let bindings = match &self.path {
+            Some(path) => Bindings::load(path).unwrap_or_else(|err| {
+                warn!("using the default bindings: {:?}", err);
+                Bindings::default()
+            }),
+            None => Bindings::default(),
+        };
+        app.insert_resource(bindings)
+            .insert_resource(BindingsFile(self.path.clone()))
+            .init_resource::<Actions>()
+            .init_resource::<StickSettings>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                update_actions.label(ActionsSystem).after(InputSystem),
+            );
+    }
+}
+
+fn update_actions(
+    bindings: Res<Bindings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut actions: ResMut<Actions>,
+) {
+    actions.reset_all();
+    for (&action, binding) in bindings.actions.iter() {
+        let pressed = binding.keys.iter().any(|&key| keyboard_input.pressed(key))
+            || gamepad_input
+                .get_pressed()
+                .any(|button| binding.buttons.contains(&button.1));
+        let just_pressed = binding
+            .keys
+            .iter()
+            .any(|&key| keyboard_input.just_pressed(key))
+            || gamepad_input
+                .get_just_pressed()
+                .any(|button| binding.buttons.contains(&button.1));
+        if pressed {
+            actions.pressed.insert(action);
+        }
+        if just_pressed {
+            actions.just_pressed.insert(action);
+        }
+    }
+}
+
+/// How the analog sticks of a gamepad are read.
+#[derive(Debug, Clone, Copy)]
+pub struct StickSettings {
+    /// A stick pushed less than this far counts as not pushed at all, so worn sticks that do not
+    /// quite come back to the middle do not make the crow creep along.
+    pub dead_zone: f32,
+    /// A stick pushed at least this far counts as pushed all the way.
+    pub saturation: f32,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        Self {
+            dead_zone: 0.2,
+            saturation: 0.9,
+        }
+    }
+}
+
+impl StickSettings {
+    /// Maps a raw axis value to -1..=1, with the dead zone at 0 and everything past `saturation`
+    /// at the ends. Without room between the two, any push past the dead zone is all the way.
+    pub fn apply(&self, value: f32) -> f32 {
+        if self.saturation <= self.dead_zone {
+            return if value.abs() > self.dead_zone {
+                value.signum()
+            } else {
+                0.0
+            };
+        }
+        let pushed = (value.abs() - self.dead_zone) / (self.saturation - self.dead_zone);
+        pushed.clamp(0.0, 1.0) * value.signum()
+    }
+}
+
+/// Where the left stick of the connected gamepad that is pushed the furthest points, after the
+/// dead zone, or zero without gamepads.
+pub(crate) fn stick_position(
+    gamepads: &Gamepads,
+    axes: &Axis<GamepadAxis>,
+    settings: &StickSettings,
+) -> Vec2 {
+    gamepads
+        .iter()
+        .map(|&gamepad| {
+            let axis = |axis_type| {
+                let value = axes.get(GamepadAxis(gamepad, axis_type)).unwrap_or(0.0);
+                settings.apply(value)
+            };
+            Vec2::new(
+                axis(GamepadAxisType::LeftStickX),
+                axis(GamepadAxisType::LeftStickY),
+            )
+        })
+        .fold(Vec2::ZERO, |furthest, stick| {
+            if stick.length_squared() > furthest.length_squared() {
+                stick
+            } else {
+                furthest
+            }
+        })
+}
+
+/// The crow's controls are written from the player's actions in this system. Other sources of
+/// controls run before it, so that the player wins where both are around.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct PlayerControlsSystem;
+
+/// Drives the crow from the player's actions and the left stick of any connected gamepad. Moving
+/// down, or pushing the stick down, dives.
+pub struct PlayerInputPlugin;
+
+impl Plugin for PlayerInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrowControls>()
+            .init_resource::<StickSettings>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                player_controls
+                    .label(PlayerControlsSystem)
+                    .after(ActionsSystem),
+            );
+    }
+}
+
+fn player_controls(
+    actions: Res<Actions>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    settings: Res<StickSettings>,
+    mut controls: ResMut<CrowControls>,
+) {
+    let stick = stick_position(&gamepads, &axes, &settings);
+    *controls = CrowControls {
+        left: actions.pressed(Action::MoveLeft),
+        right: actions.pressed(Action::MoveRight),
+        flap: actions.pressed(Action::Flap),
+        dive: actions.pressed(Action::MoveDown) || stick.y <= -STICK_DIVE,
+        stick: stick.x,
+    };
+}
@@ -0,0 +1,277 @@
+use bevy::prelude::*;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use crate::crow::{Carried, CROW_SIZE};
+use crate::level::{CurrentLevel, Level};
+use crate::state::{Difficulty, GameState, GameplayTick, TIME_STEP};
+use crate::world::{Collider, ColliderType, LevelObject, RunEntity};
+
+// spots tried for every new jewel before giving up until the next one is due
+const SPAWN_ATTEMPTS: usize = 20;
+
+// seconds of play since the last jewel was spawned
+#[derive(Default)]
+struct JewelSpawnTimer(f32);
+
+/// Something worth stealing. Heavier jewels slow the crow down more while it carries them.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Jewel {
+    pub kind: JewelKind,
+    /// Points for delivering the jewel to a nest.
+    pub value: usize,
+    /// From 0 for something weightless to 1 for the heaviest jewel there is.
+    pub weight: f32,
+    /// How often the jewel shows up compared to the other kinds.
+    pub rarity: u32,
+}
+
+impl Jewel {
+    pub fn new(kind: JewelKind) -> Self {
+        Self {
+            kind,
+            value: kind.value(),
+            weight: kind.weight(),
+            rarity: kind.rarity(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JewelKind {
+    Coin,
+    Ring,
+    Necklace,
+    Crown,
+}
+
+impl JewelKind {
+    pub const ALL: [JewelKind; 4] = [
+        JewelKind::Coin,
+        JewelKind::Ring,
+        JewelKind::Necklace,
+        JewelKind::Crown,
+    ];
+
+    pub fn value(self) -> usize {
+        match self {
+            JewelKind::Coin => 1,
+            JewelKind::Ring => 3,
+            JewelKind::Necklace => 5,
+            JewelKind::Crown => 10,
+        }
+    }
+
+    pub fn weight(self) -> f32 {
+        match self {
+            JewelKind::Coin => 0.25,
+            JewelKind::Ring => 0.5,
+            JewelKind::Necklace => 0.75,
+            JewelKind::Crown => 1.0,
+        }
+    }
+
+    pub fn rarity(self) -> u32 {
+        match self {
+            JewelKind::Coin => 50,
+            JewelKind::Ring => 30,
+            JewelKind::Necklace => 15,
+            JewelKind::Crown => 5,
+        }
+    }
+
+    pub(crate) fn size(self) -> Vec2 {
+        match self {
+            JewelKind::Coin => Vec2::new(32.0, 32.0),
+            JewelKind::Ring => Vec2::new(64.0, 64.0),
+            JewelKind::Necklace => Vec2::new(64.0, 64.0),
+            JewelKind::Crown => Vec2::new(80.0, 80.0),
+        }
+    }
+
+    // every kind is drawn with the ring sprite for now, told apart by colour and size
+    fn tint(self) -> Color {
+        match self {
+            JewelKind::Coin => Color::rgb(1.0, 0.8, 0.2),
+            JewelKind::Ring => Color::WHITE,
+            JewelKind::Necklace => Color::rgb(0.5, 0.8, 1.0),
+            JewelKind::Crown => Color::rgb(1.0, 0.6, 0.1),
+        }
+    }
+}
+
+/// Places the level's starting jewels and keeps spawning new ones in free spots while a run is
+/// in progress, up to the level's `JewelSpawns::max_jewels` at a time.
+pub struct JewelPlugin;
+
+impl Plugin for JewelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JewelSpawnTimer>()
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(spawn_start_jewels))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(GameplayTick)
+                    .with_system(spawn_random_jewel),
+            );
+    }
+}
+
+fn spawn_start_jewels(
+    mut commands: Commands,
+    mut timer: ResMut<JewelSpawnTimer>,
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+) {
+    let Some(level) = levels.get(&current_level.0) else {
+        return;
+    };
+    timer.0 = 0.0;
+    for position in level.jewels.iter() {
+        let jewel = spawn_jewel(&mut commands, &asset_server, JewelKind::Ring, *position);
+        commands.entity(jewel).insert(LevelObject {
+            collider_type: ColliderType::Jewel,
+            position: *position,
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_random_jewel(
+    mut commands: Commands,
+    mut timer: ResMut<JewelSpawnTimer>,
+    difficulty: Res<Difficulty>,
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    jewel_query: Query<(), (With<Jewel>, Without<Carried>)>,
+    collider_query: Query<(&Collider, &Transform)>,
+) {
+    timer.0 += TIME_STEP;
+    if timer.0 < spawn_interval(*difficulty) {
+        return;
+    }
+    timer.0 = 0.0;
+    let Some(level) = levels.get(&current_level.0) else {
+        return;
+    };
+    let spawns = level.jewel_spawns;
+    if jewel_query.iter().count() >= spawns.max_jewels {
+        return;
+    }
+
+    let mut surfaces = Vec::new();
+    let mut obstacles = Vec::new();
+    for (collider, transform) in collider_query.iter() {
+        let collider_box = (
+            transform.translation.truncate(),
+            Vec2::new(collider.width, collider.height),
+        );
+        match collider.collider_type {
+            ColliderType::Surface => {
+                surfaces.push(collider_box);
+                obstacles.push(collider_box);
+            }
+            ColliderType::Jewel | ColliderType::Nest => obstacles.push(collider_box),
+            // people walk away soon enough
+            ColliderType::Person => {}
+        }
+    }
+
+    let mut rng = rand::thread_rng();
+    let kind = pick_jewel_kind(&mut rng);
+    let min_x = spawns.min_x.max(level.bounds.min_x);
+    let max_x = spawns.max_x.min(level.bounds.max_x);
+    if min_x > max_x {
+        warn!("the level's jewel spawns are outside of its bounds, no jewels are spawned");
+        return;
+    }
+    for _ in 0..SPAWN_ATTEMPTS {
+        let x = rng.gen_range(min_x..=max_x);
+        let (spot, crow_spot) = if spawns.on_surfaces {
+            // dropped from the spawn height onto whatever is below, spots with nothing below
+            // would leave the jewel falling out of the level, and the jewel has to rest on it
+            // with its whole width instead of hanging over an edge
+            let Some(top) = surfaces
+                .iter()
+                .filter(|(center, size)| {
+                    (center.x - x).abs() <= (size.x - kind.size().x) / 2.0
+                        && center.y + size.y / 2.0 <= spawns.y
+                })
+                .map(|(center, size)| center.y + size.y / 2.0)
+                .reduce(f32::max)
+            else {
+                continue;
+            };
+            (
+                Vec2::new(x, top + kind.size().y / 2.0),
+                Vec2::new(x, top + CROW_SIZE.y / 2.0),
+            )
+        } else {
+            (Vec2::new(x, spawns.y), Vec2::new(x, spawns.y))
+        };
+        // the crow has to fit where the jewel lies to pick it up, so spots without room for it
+        // under a low ceiling or between walls are never reachable
+        let reachable = surfaces
+            .iter()
+            .all(|(center, size)| !overlaps(crow_spot, CROW_SIZE, *center, *size));
+        if reachable
+            && obstacles
+                .iter()
+                .all(|(center, size)| !overlaps(spot, kind.size(), *center, *size))
+        {
+            spawn_jewel(&mut commands, &asset_server, kind, spot);
+            return;
+        }
+    }
+}
+
+// Whether two boxes overlap, boxes that only touch do not.
+fn overlaps(a: Vec2, a_size: Vec2, b: Vec2, b_size: Vec2) -> bool {
+    let overlap = (a_size + b_size) / 2.0 - (a - b).abs();
+    overlap.x > 0.01 && overlap.y > 0.01
+}
+
+/// Seconds between new jewels, fewer of them turn up the harder the game is.
+pub fn spawn_interval(difficulty: Difficulty) -> f32 {
+    match difficulty {
+        Difficulty::Easy => 3.0,
+        Difficulty::Normal => 5.0,
+        Difficulty::Hard => 8.0,
+    }
+}
+
+/// Picks a kind of jewel with a chance proportional to its rarity.
+pub fn pick_jewel_kind(rng: &mut impl Rng) -> JewelKind {
+    let kinds = JewelKind::ALL;
+    let rarities = WeightedIndex::new(kinds.iter().map(|kind| kind.rarity())).unwrap();
+    kinds[rarities.sample(rng)]
+}
+
+pub(crate) fn spawn_jewel(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    kind: JewelKind,
+    position: Vec2,
+) -> Entity {
+    let size = kind.size();
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load("ring.png"),
+            sprite: Sprite {
+                color: kind.tint(),
+                custom_size: Some(size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(position.extend(1.0)),
+            ..Default::default()
+        })
+        .insert(Collider {
+            width: size.x,
+            height: size.y,
+            collider_type: ColliderType::Jewel,
+        })
+        .insert(Jewel::new(kind))
+        .insert(RunEntity)
+        .id()
+}
@@ -0,0 +1,242 @@
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadState, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use serde::{Deserialize, Serialize};
+
+use crate::state::GameState;
+
+pub const DEFAULT_LEVEL: &str = "levels/level1.level.ron";
+
+/// A level as designed in a `.level.ron` file under `assets/levels`.
+#[derive(Debug, Clone, Deserialize, Serialize, TypeUuid)]
+#[uuid = "034b4155-0213-49f6-b5dc-71b3a75a5b7c"]
+pub struct Level {
+    pub background: String,
+    pub bounds: Bounds,
+    pub crow_spawn: Vec2,
+    pub surfaces: Vec<Surface>,
+    #[serde(default)]
+    pub decorations: Vec<Decoration>,
+    /// Jewels that are already lying around when the run starts.
+    #[serde(default)]
+    pub jewels: Vec<Vec2>,
+    /// Where the crow brings its jewels to score them.
+    #[serde(default)]
+    pub nests: Vec<Vec2>,
+    pub jewel_spawns: JewelSpawns,
+    pub people: PeopleSpawns,
+}
+
+/// How far the crow may travel horizontally.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct Bounds {
+    pub min_x: f32,
+    pub max_x: f32,
+}
+
+/// A solid block the crow can stand on and bump into.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Surface {
+    pub texture: String,
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+/// Something drawn in the world that nothing collides with.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum Decoration {
+    Sprite {
+        texture: String,
+        position: Vec2,
+        size: Vec2,
+    },
+    Text {
+        text: String,
+        position: Vec2,
+        font_size: f32,
+    },
+}
+
+/// Where new jewels show up while the run goes on.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct JewelSpawns {
+    pub min_x: f32,
+    pub max_x: f32,
+    /// The height jewels appear at, or the highest they can land at with `on_surfaces`.
+    pub y: f32,
+    /// Drop new jewels onto the surfaces below `y` instead of leaving them in the air.
+    #[serde(default)]
+    pub on_surfaces: bool,
+    /// No more jewels are spawned while this many are lying around.
+    #[serde(default = "default_max_jewels")]
+    pub max_jewels: usize,
+}
+
+fn default_max_jewels() -> usize {
+    10
+}
+
+/// `count` people are placed at a random horizontal distance on either side of the crow's
+/// spawn, in addition to anyone standing at one of the fixed `positions`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PeopleSpawns {
+    pub count: usize,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub y: f32,
+    /// Seed for the people's positions, so that every run of a level starts out the same.
+    pub seed: u64,
+    #[serde(default)]
+    pub positions: Vec<Vec2>,
+    /// How far to either side of where they start people walk on their patrol, 0 keeps them
+    /// standing in place.
+    #[serde(default = "default_patrol_distance")]
+    pub patrol_distance: f32,
+    /// Speeds of a typical person, every randomly placed person is a little faster or slower.
+    #[serde(default)]
+    pub speeds: PersonSpeeds,
+}
+
+fn default_patrol_distance() -> f32 {
+    150.0
+}
+
+/// How fast a person walks, in pixels per second.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct PersonSpeeds {
+    /// Patrolling and going back to their post.
+    pub walk: f32,
+    pub chase: f32,
+}
+
+impl Default for PersonSpeeds {
+    fn default() -> Self {
+        Self {
+            walk: 25.0,
+            chase: 60.0,
+        }
+    }
+}
+
+/// The asset paths of the levels that ship in `levels` under `asset_folder`, in name order.
+/// Falls back to just `DEFAULT_LEVEL` if the folder cannot be read.
+pub fn level_paths(asset_folder: &str) -> Vec<String> {
+    let mut paths = find_levels(asset_folder).unwrap_or_else(|err| {
+        warn!("could not list the levels: {}", err);
+        Vec::new()
+    });
+    if paths.is_empty() {
+        paths.push(DEFAULT_LEVEL.to_string());
+    }
+    paths.sort();
+    paths
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn find_levels(asset_folder: &str) -> anyhow::Result<Vec<String>> {
+    let dir = bevy::asset::FileAssetIo::get_root_path()
+        .join(asset_folder)
+        .join("levels");
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if name.ends_with(".level.ron") {
+            paths.push(format!("levels/{}", name));
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn find_levels(_asset_folder: &str) -> anyhow::Result<Vec<String>> {
+    anyhow::bail!("levels cannot be listed in the browser")
+}
+
+/// The name a level is shown under, its file name without the extension.
+pub fn level_name(path: &str) -> &str {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    file_name.trim_end_matches(".level.ron")
+}
+
+/// The level that is being played. Insert it before adding `LevelPlugin` to pick another level.
+pub struct CurrentLevel(pub Handle<Level>);
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let level: Level = ron::de::from_bytes(bytes)?;
+            if level.people.min_distance > level.people.max_distance {
+                anyhow::bail!("people.min_distance is larger than people.max_distance");
+            }
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+// the last level that finished loading, to fall back to when another one fails to load
+#[derive(Default)]
+struct LastLoadedLevel(Option<Handle<Level>>);
+
+/// Loads the current level and holds the game in `GameState::Loading` until it is ready, then
+/// opens the main menu. A level that fails to load is logged and the last level that did load
+/// is kept, if there is one.
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .init_resource::<LastLoadedLevel>()
+            .add_startup_system(load_level)
+            .add_system_set(SystemSet::on_update(GameState::Loading).with_system(wait_for_level));
+    }
+}
+
+fn load_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    current_level: Option<Res<CurrentLevel>>,
+) {
+    // edited level files are picked up by the running game, see `WorldPlugin`
+    if let Err(err) = asset_server.watch_for_changes() {
+        warn!("level files will not be hot reloaded: {:?}", err);
+    }
+    if current_level.is_none() {
+        commands.insert_resource(CurrentLevel(asset_server.load(DEFAULT_LEVEL)));
+    }
+}
+
+fn wait_for_level(
+    mut current_level: ResMut<CurrentLevel>,
+    mut last_loaded: ResMut<LastLoadedLevel>,
+    asset_server: Res<AssetServer>,
+    levels: Res<Assets<Level>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if levels.get(&current_level.0).is_some() {
+        last_loaded.0 = Some(current_level.0.clone());
+        let _ = state.set(GameState::MainMenu);
+    } else if asset_server.get_load_state(&current_level.0) == LoadState::Failed {
+        let path = asset_server.get_handle_path(&current_level.0);
+        error!(
+            "could not load the level {:?}",
+            path.map(|path| path.path().to_owned())
+        );
+        if let Some(handle) = &last_loaded.0 {
+            current_level.0 = handle.clone();
+        }
+        let _ = state.set(GameState::MainMenu);
+    }
+}
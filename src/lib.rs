@@ -0,0 +1,77 @@
+use bevy::app::PluginGroupBuilder;
+use bevy::prelude::*;
+
+mod audio;
+mod crow;
+mod editor;
+mod headless;
+mod highscore;
+mod hud;
+mod input;
+mod jewel;
+mod level;
+mod menu;
+mod noise;
+mod people;
+mod physics;
+mod spatial;
+mod state;
+mod world;
+
+pub use audio::AudioFxPlugin;
+pub use crow::{
+    Carried, Crow, CrowControls, CrowFlapped, CrowFlight, CrowPlugin, CrowState, Health, Stamina,
+};
+pub use editor::{EditorBrush, EditorCursor, EditorPlugin};
+pub use headless::{HeadlessPlugins, ScriptedInput};
+pub use highscore::{
+    default_high_score_path, format_date, HighScoreEntry, HighScoreFile, HighScorePlugin,
+    HighScores, PendingHighScore, RunTime, MAX_HIGH_SCORES, MAX_NAME_LENGTH,
+};
+pub use hud::{
+    high_score_table, GameOverText, GameOverUI, HudPlugin, LivesText, NoiseMeter, ScoreText,
+    StaminaMeter,
+};
+pub use input::{
+    default_bindings_path, Action, Actions, ActionsPlugin, ActionsSystem, Binding, Bindings,
+    BindingsFile, PlayerControlsSystem, PlayerInputPlugin, StickSettings,
+};
+pub use jewel::{pick_jewel_kind, spawn_interval, Jewel, JewelKind, JewelPlugin};
+pub use level::{
+    level_name, level_paths, Bounds, CurrentLevel, Decoration, JewelSpawns, Level, LevelLoader,
+    LevelPlugin, PeopleSpawns, PersonSpeeds, Surface, DEFAULT_LEVEL,
+};
+pub use menu::{Menu, MenuItem, MenuPlugin, MenuScreen};
+pub use noise::{Noise, NoiseKind, NoiseLevel, NoisePlugin};
+pub use people::{PeoplePlugin, Person, PersonState};
+pub use physics::{sweep, Contacts, KinematicBody, PhysicsPlugin, PhysicsSystem, SweepHit};
+pub use spatial::SpatialHash;
+pub use state::{Difficulty, GameState, GameStatePlugin, GameplayTick, TIME_STEP};
+pub use world::{
+    AnimationTimer, Background, BirdCamera, Collider, ColliderType, LevelGeometry, LevelObject,
+    RunEntity, SurfaceTexture, WorldPlugin,
+};
+
+/// All of the game's plugins. Expects `DefaultPlugins` and `bevy_kira_audio::AudioPlugin`.
+pub struct CrowJewelsPlugins;
+
+impl PluginGroup for CrowJewelsPlugins {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        group
+            .add(GameStatePlugin::default())
+            .add(LevelPlugin)
+            .add(WorldPlugin)
+            .add(PhysicsPlugin)
+            .add(CrowPlugin)
+            .add(PeoplePlugin)
+            .add(NoisePlugin)
+            .add(JewelPlugin)
+            .add(HighScorePlugin::default())
+            .add(HudPlugin)
+            .add(MenuPlugin)
+            .add(ActionsPlugin::default())
+            .add(PlayerInputPlugin)
+            .add(EditorPlugin)
+            .add(AudioFxPlugin);
+    }
+}
@@ -0,0 +1,446 @@
+use bevy::app::AppExit;
+use bevy::asset::AssetServerSettings;
+use bevy::prelude::*;
+
+use crate::highscore::HighScores;
+use crate::hud::high_score_table;
+use crate::input::{stick_position, Action, Actions, Bindings, BindingsFile, StickSettings};
+use crate::level::{level_name, level_paths, CurrentLevel};
+use crate::state::{Difficulty, GameState};
+use crate::world::RunEntity;
+
+/// A page of the main menu, or the pause menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuScreen {
+    Main,
+    LevelSelect,
+    Settings,
+    Controls,
+    HighScores,
+    Pause,
+}
+
+/// Something that can be picked in a menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuItem {
+    Play,
+    LevelSelect,
+    Settings,
+    HighScores,
+    Quit,
+    /// Plays the level at this asset path from now on.
+    Level(String),
+    /// Cycles through the difficulties.
+    Difficulty,
+    Controls,
+    /// Binds the next key or gamepad button pressed to this action.
+    Rebind(Action),
+    ResetBindings,
+    Back,
+    Resume,
+    MainMenu,
+}
+
+/// The menu page on screen and which of its items is highlighted.
+pub struct Menu {
+    pub screen: MenuScreen,
+    pub items: Vec<MenuItem>,
+    pub selected: usize,
+    /// The action waiting for a key or button to be pressed on the controls page.
+    pub rebinding: Option<Action>,
+}
+
+impl Menu {
+    pub fn new(screen: MenuScreen, levels: &[String]) -> Self {
+        let items = match screen {
+            MenuScreen::Main => vec![
+                MenuItem::Play,
+                MenuItem::LevelSelect,
+                MenuItem::Settings,
+                MenuItem::HighScores,
+                MenuItem::Quit,
+            ],
+            MenuScreen::LevelSelect => levels
+                .iter()
+                .cloned()
+                .map(MenuItem::Level)
+                .chain([MenuItem::Back])
+                .collect(),
+            MenuScreen::Settings => vec![MenuItem::Difficulty, MenuItem::Controls, MenuItem::Back],
+            MenuScreen::Controls => Action::ALL
+                .into_iter()
+                .map(MenuItem::Rebind)
+                .chain([MenuItem::ResetBindings, MenuItem::Back])
+                .collect(),
+            MenuScreen::HighScores => vec![MenuItem::Back],
+            MenuScreen::Pause => vec![MenuItem::Resume, MenuItem::MainMenu, MenuItem::Quit],
+        };
+        Self {
+            screen,
+            items,
+            selected: 0,
+            rebinding: None,
+        }
+    }
+
+    pub fn selected_item(&self) -> &MenuItem {
+        &self.items[self.selected]
+    }
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Self::new(MenuScreen::Main, &[])
+    }
+}
+
+#[derive(Component)]
+struct MenuUI;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+enum MenuSystem {
+    Rebind,
+    Navigate,
+}
+
+/// The main menu the game opens with and the pause menu [Escape] brings up during a run.
+///
+/// The `MoveUp` and `MoveDown` actions or the left stick move through the items, `Interact` picks
+/// one, `MoveLeft` and `MoveRight` change a setting and `Back` goes back. The controls page under
+/// settings rebinds the actions and saves them to the bindings file.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Menu>()
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(open_main_menu))
+            .add_system_set(SystemSet::on_enter(GameState::Paused).with_system(open_pause_menu))
+            .add_system_set(
+                SystemSet::on_update(GameState::MainMenu)
+                    .with_system(capture_binding.label(MenuSystem::Rebind))
+                    .with_system(
+                        navigate_menu
+                            .label(MenuSystem::Navigate)
+                            .after(MenuSystem::Rebind),
+                    )
+                    .with_system(draw_menu.after(MenuSystem::Navigate)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(capture_binding.label(MenuSystem::Rebind))
+                    .with_system(
+                        navigate_menu
+                            .label(MenuSystem::Navigate)
+                            .after(MenuSystem::Rebind),
+                    )
+                    .with_system(draw_menu.after(MenuSystem::Navigate)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::MainMenu).with_system(despawn_menu))
+            .add_system_set(SystemSet::on_exit(GameState::Paused).with_system(despawn_menu));
+    }
+}
+
+fn open_main_menu(mut menu: ResMut<Menu>) {
+    *menu = Menu::new(MenuScreen::Main, &[]);
+}
+
+fn open_pause_menu(mut menu: ResMut<Menu>) {
+    *menu = Menu::new(MenuScreen::Pause, &[]);
+}
+
+// how far a stick has to be pushed to move through a menu
+const STICK_FLICK: f32 = 0.5;
+
+// Which way the player pushed this frame, from their actions or the stick of any gamepad.
+struct MenuInput {
+    up: bool,
+    down: bool,
+    left: bool,
+    right: bool,
+    select: bool,
+    back: bool,
+}
+
+impl MenuInput {
+    // `stick` and `last_stick` are where the stick points this frame and the last, a stick only
+    // counts once when it is pushed past halfway and not again until it is let go.
+    fn read(actions: &Actions, stick: Vec2, last_stick: Vec2) -> Self {
+        let flick = |now: f32, before: f32| now > STICK_FLICK && before <= STICK_FLICK;
+        Self {
+            up: actions.just_pressed(Action::MoveUp) || flick(stick.y, last_stick.y),
+            down: actions.just_pressed(Action::MoveDown) || flick(-stick.y, -last_stick.y),
+            left: actions.just_pressed(Action::MoveLeft) || flick(-stick.x, -last_stick.x),
+            right: actions.just_pressed(Action::MoveRight) || flick(stick.x, last_stick.x),
+            select: actions.just_pressed(Action::Interact),
+            back: actions.just_pressed(Action::Back) || actions.just_pressed(Action::Pause),
+        }
+    }
+}
+
+// Binds whatever is pressed next to the action picked on the controls page.
+fn capture_binding(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut menu: ResMut<Menu>,
+    mut bindings: ResMut<Bindings>,
+    mut actions: ResMut<Actions>,
+    file: Res<BindingsFile>,
+) {
+    let Some(action) = menu.rebinding else {
+        return;
+    };
+    let key = keyboard_input.get_just_pressed().next().copied();
+    let button = gamepad_input
+        .get_just_pressed()
+        .next()
+        .map(|button| button.1);
+    if key.is_none() && button.is_none() {
+        return;
+    }
+    let binding = bindings.actions.entry(action).or_default();
+    if let Some(key) = key {
+        binding.keys = vec![key];
+    }
+    if let Some(button) = button {
+        binding.buttons = vec![button];
+    }
+    menu.rebinding = None;
+    // the menu must not act on the key that was just bound
+    actions.reset_all();
+    save_bindings(&bindings, &file);
+}
+
+fn save_bindings(bindings: &Bindings, file: &BindingsFile) {
+    if let Some(path) = &file.0 {
+        if let Err(err) = bindings.save(path) {
+            error!("could not save the bindings: {:?}", err);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn navigate_menu(
+    mut actions: ResMut<Actions>,
+    mut menu: ResMut<Menu>,
+    mut bindings: ResMut<Bindings>,
+    bindings_file: Res<BindingsFile>,
+    mut difficulty: ResMut<Difficulty>,
+    mut state: ResMut<State<GameState>>,
+    mut commands: Commands,
+    mut app_exit: EventWriter<AppExit>,
+    mut current_level: ResMut<CurrentLevel>,
+    asset_server: Res<AssetServer>,
+    asset_settings: Option<Res<AssetServerSettings>>,
+    run_query: Query<Entity, With<RunEntity>>,
+    (gamepads, axes, stick_settings): (Res<Gamepads>, Res<Axis<GamepadAxis>>, Res<StickSettings>),
+    mut last_stick: Local<Vec2>,
+) {
+    let stick = stick_position(&gamepads, &axes, &stick_settings);
+    let input = MenuInput::read(&actions, stick, *last_stick);
+    *last_stick = stick;
+    if menu.rebinding.is_some() {
+        return;
+    }
+    let count = menu.items.len();
+    if input.up {
+        menu.selected = (menu.selected + count - 1) % count;
+    }
+    if input.down {
+        menu.selected = (menu.selected + 1) % count;
+    }
+    if (input.left || input.right) && *menu.selected_item() == MenuItem::Difficulty {
+        *difficulty = cycle_difficulty(*difficulty, input.right);
+    }
+
+    let item = if input.back {
+        match menu.screen {
+            MenuScreen::Main => return,
+            MenuScreen::Pause => MenuItem::Resume,
+            _ => MenuItem::Back,
+        }
+    } else if input.select {
+        menu.selected_item().clone()
+    } else {
+        return;
+    };
+    // the state we switch to must not see the same press again
+    actions.reset_all();
+
+    match item {
+        MenuItem::Play => {
+            let _ = state.set(GameState::Playing);
+        }
+        MenuItem::LevelSelect => {
+            let asset_folder = asset_settings.map_or("assets".to_string(), |settings| {
+                settings.asset_folder.clone()
+            });
+            *menu = Menu::new(MenuScreen::LevelSelect, &level_paths(&asset_folder));
+        }
+        MenuItem::Settings => *menu = Menu::new(MenuScreen::Settings, &[]),
+        MenuItem::HighScores => *menu = Menu::new(MenuScreen::HighScores, &[]),
+        MenuItem::Quit => app_exit.send(AppExit),
+        MenuItem::Level(path) => {
+            // the main menu comes back once the level has loaded
+            current_level.0 = asset_server.load(path.as_str());
+            let _ = state.set(GameState::Loading);
+        }
+        MenuItem::Difficulty => *difficulty = cycle_difficulty(*difficulty, true),
+        MenuItem::Controls => *menu = Menu::new(MenuScreen::Controls, &[]),
+        MenuItem::Rebind(action) => menu.rebinding = Some(action),
+        MenuItem::ResetBindings => {
+            *bindings = Bindings::default();
+            save_bindings(&bindings, &bindings_file);
+        }
+        MenuItem::Back if menu.screen == MenuScreen::Controls => {
+            *menu = Menu::new(MenuScreen::Settings, &[]);
+            menu.selected = 1;
+        }
+        MenuItem::Back => *menu = Menu::new(MenuScreen::Main, &[]),
+        MenuItem::Resume => {
+            let _ = state.pop();
+        }
+        MenuItem::MainMenu => {
+            for entity in run_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            let _ = state.replace(GameState::MainMenu);
+        }
+    }
+}
+
+fn cycle_difficulty(difficulty: Difficulty, harder: bool) -> Difficulty {
+    match (difficulty, harder) {
+        (Difficulty::Easy, true) | (Difficulty::Hard, false) => Difficulty::Normal,
+        (Difficulty::Normal, true) | (Difficulty::Easy, false) => Difficulty::Hard,
+        (Difficulty::Hard, true) | (Difficulty::Normal, false) => Difficulty::Easy,
+    }
+}
+
+fn item_label(
+    item: &MenuItem,
+    difficulty: Difficulty,
+    bindings: &Bindings,
+    asset_server: &AssetServer,
+    current_level: &CurrentLevel,
+) -> String {
+    match item {
+        MenuItem::Play => "Play".to_string(),
+        MenuItem::LevelSelect => "Level select".to_string(),
+        MenuItem::Settings => "Settings".to_string(),
+        MenuItem::HighScores => "High scores".to_string(),
+        MenuItem::Quit => "Quit".to_string(),
+        MenuItem::Level(path) => {
+            let current = asset_server
+                .get_handle_path(&current_level.0)
+                .is_some_and(|current| current.path().to_string_lossy() == path.as_str());
+            if current {
+                format!("{} (current)", level_name(path))
+            } else {
+                level_name(path).to_string()
+            }
+        }
+        MenuItem::Difficulty => format!("Difficulty: < {:?} >", difficulty),
+        MenuItem::Controls => "Controls".to_string(),
+        MenuItem::Rebind(action) => format!("{:?}: {}", action, bindings.describe(*action)),
+        MenuItem::ResetBindings => "Reset to defaults".to_string(),
+        MenuItem::Back => "Back".to_string(),
+        MenuItem::Resume => "Resume".to_string(),
+        MenuItem::MainMenu => "Main menu".to_string(),
+    }
+}
+
+// Rebuilds the menu whenever the page, the highlighted item or something it shows changes.
+#[allow(clippy::too_many_arguments)]
+fn draw_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    menu: Res<Menu>,
+    difficulty: Res<Difficulty>,
+    bindings: Res<Bindings>,
+    high_scores: Res<HighScores>,
+    current_level: Res<CurrentLevel>,
+    ui_query: Query<Entity, With<MenuUI>>,
+) {
+    let drawn = ui_query.iter().next().is_some();
+    let changed = menu.is_changed()
+        || difficulty.is_changed()
+        || bindings.is_changed()
+        || high_scores.is_changed();
+    if drawn && !changed {
+        return;
+    }
+    for entity in ui_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let font: Handle<Font> = asset_server.load("Inconsolata-Regular.ttf");
+    let text = |value: String, font_size: f32, color: Color| TextBundle {
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color,
+            },
+            Default::default(),
+        ),
+        ..Default::default()
+    };
+    let title = match menu.screen {
+        MenuScreen::Main => "Crow Jewels",
+        MenuScreen::LevelSelect => "Level select",
+        MenuScreen::Settings => "Settings",
+        MenuScreen::Controls => "Controls",
+        MenuScreen::HighScores => "High scores",
+        MenuScreen::Pause => "Paused",
+    };
+    let show_table = matches!(menu.screen, MenuScreen::Main | MenuScreen::HighScores);
+    // every action gets a line on the controls page, which would not fit at the usual size
+    let item_size = if menu.screen == MenuScreen::Controls {
+        28.0
+    } else {
+        40.0
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                // columns are laid out bottom up, so the title goes last
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.9, 0.9, 0.8, 0.9).into(),
+            ..Default::default()
+        })
+        .insert(MenuUI)
+        .with_children(|parent| {
+            parent.spawn_bundle(text(title.to_string(), 60.0, Color::BLACK));
+            for (index, item) in menu.items.iter().enumerate() {
+                let label = match (item, menu.rebinding) {
+                    (MenuItem::Rebind(action), Some(rebinding)) if *action == rebinding => {
+                        format!("{:?}: press a key or button", action)
+                    }
+                    _ => item_label(item, *difficulty, &bindings, &asset_server, &current_level),
+                };
+                let (label, color) = if index == menu.selected {
+                    (format!("> {} <", label), Color::rgb(0.7, 0.1, 0.1))
+                } else {
+                    (label, Color::BLACK)
+                };
+                parent.spawn_bundle(text(label, item_size, color));
+            }
+            if show_table {
+                parent.spawn_bundle(text(high_score_table(&high_scores), 20.0, Color::BLACK));
+            }
+        });
+}
+
+fn despawn_menu(mut commands: Commands, ui_query: Query<Entity, With<MenuUI>>) {
+    for entity in ui_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+
+use crate::crow::{Crow, CrowFlapped, CrowState};
+use crate::physics::{Contacts, PhysicsSystem};
+use crate::state::{GameState, GameplayTick, TIME_STEP};
+
+// seconds between two footsteps of a running crow
+const FOOTSTEP_INTERVAL: f32 = 0.3;
+const FOOTSTEP_RADIUS: f32 = 200.0;
+const FLAP_RADIUS: f32 = 450.0;
+// seconds between two rushes of air while the crow dives
+const DIVE_NOISE_INTERVAL: f32 = 0.2;
+const DIVE_RADIUS: f32 = 400.0;
+// how quickly the noise meter falls back to silence, per second, as a fraction of the full meter
+const NOISE_DECAY: f32 = 0.5;
+
+/// Something people can hear if they are within `radius` of `position`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Noise {
+    pub position: Vec2,
+    pub radius: f32,
+    pub kind: NoiseKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseKind {
+    Footstep,
+    Flap,
+    Dive,
+}
+
+/// How loud the crow has been lately, from 0 for silent to 1 for a flap right now.
+#[derive(Default)]
+pub struct NoiseLevel(pub f32);
+
+// seconds since the crow's last footstep and last rush of air in a dive
+struct NoiseTimers {
+    footstep: f32,
+    dive: f32,
+}
+
+impl Default for NoiseTimers {
+    // the first step and the start of a dive are heard straight away
+    fn default() -> Self {
+        Self {
+            footstep: FOOTSTEP_INTERVAL,
+            dive: DIVE_NOISE_INTERVAL,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub(crate) enum NoiseSystem {
+    /// The crow's noises for this tick are sent. Listeners run after it.
+    Emit,
+}
+
+/// Turns the crow's running, flapping and diving into `Noise` events for people to hear.
+pub struct NoisePlugin;
+
+impl Plugin for NoisePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Noise>()
+            .init_resource::<NoiseLevel>()
+            .init_resource::<NoiseTimers>()
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(reset_noise))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(GameplayTick)
+                    .with_system(
+                        emit_crow_noise
+                            .label(NoiseSystem::Emit)
+                            .after(PhysicsSystem::Resolve),
+                    ),
+            );
+    }
+}
+
+fn reset_noise(mut noise_level: ResMut<NoiseLevel>, mut timers: ResMut<NoiseTimers>) {
+    noise_level.0 = 0.0;
+    *timers = NoiseTimers::default();
+}
+
+fn emit_crow_noise(
+    mut timers: ResMut<NoiseTimers>,
+    mut flap_events: EventReader<CrowFlapped>,
+    mut noise_events: EventWriter<Noise>,
+    mut noise_level: ResMut<NoiseLevel>,
+    crow_query: Query<(&Crow, &Contacts, &Transform)>,
+) {
+    noise_level.0 = (noise_level.0 - NOISE_DECAY * TIME_STEP).max(0.0);
+    let Ok((crow, contacts, transform)) = crow_query.get_single() else {
+        return;
+    };
+    let position = transform.translation.truncate();
+    let mut noises = Vec::new();
+    if flap_events.iter().count() > 0 {
+        noises.push((FLAP_RADIUS, NoiseKind::Flap));
+    }
+    if crow.crow_state == CrowState::Run && contacts.grounded {
+        timers.footstep += TIME_STEP;
+        if timers.footstep >= FOOTSTEP_INTERVAL {
+            timers.footstep = 0.0;
+            noises.push((FOOTSTEP_RADIUS, NoiseKind::Footstep));
+        }
+    } else {
+        // the first step after standing still is heard straight away
+        timers.footstep = FOOTSTEP_INTERVAL;
+    }
+    // a dive is loud from the moment it starts until the crow pulls out of it
+    if crow.crow_state == CrowState::Dive {
+        timers.dive += TIME_STEP;
+        if timers.dive >= DIVE_NOISE_INTERVAL {
+            timers.dive = 0.0;
+            noises.push((DIVE_RADIUS, NoiseKind::Dive));
+        }
+    } else {
+        timers.dive = DIVE_NOISE_INTERVAL;
+    }
+    for (radius, kind) in noises {
+        noise_level.0 = noise_level.0.max(radius / FLAP_RADIUS);
+        noise_events.send(Noise {
+            position,
+            radius,
+            kind,
+        });
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::crow::Crow;
+use crate::level::{CurrentLevel, Level, PersonSpeeds};
+use crate::noise::{Noise, NoiseSystem};
+use crate::physics::{sweep, Contacts, KinematicBody, PhysicsSystem};
+use crate::spatial::SpatialHash;
+use crate::state::{GameState, GameplayTick, TIME_STEP};
+use crate::world::{AnimationTimer, Collider, ColliderType, LevelObject, RunEntity};
+
+const PERSON_SIZE: Vec2 = bevy::math::const_vec2!([64.0, 64.0]);
+
+// how far past their feet people look for the edge of what they walk on
+const LEDGE_PROBE: f32 = 4.0;
+
+// how far away people can spot the crow in the direction they are looking
+const SIGHT_RANGE: f32 = 350.0;
+// how far above a person the crow can be and still be noticed, any higher up it is just
+// another bird in the sky
+const SIGHT_HEIGHT: f32 = 250.0;
+// seconds of watching the crow before giving chase
+const SUSPICION_TIME: f32 = 1.0;
+// seconds without seeing the crow before giving up on it
+const GIVE_UP_TIME: f32 = 3.0;
+// seconds spent standing at every waypoint
+const IDLE_TIME: f32 = 1.5;
+// close enough to a target to count as being there
+const ARRIVE_DISTANCE: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersonState {
+    /// Standing around, or waiting at a waypoint before walking on.
+    Idle,
+    /// Walking to the next waypoint.
+    Patrol,
+    /// Saw the crow and is watching to make sure.
+    Suspicious,
+    /// Running after the crow, or to where it was last seen.
+    Chase,
+    /// Lost the crow and is going back to where they were before.
+    Return,
+    /// Heard something and is going to have a look.
+    Investigate,
+}
+
+#[derive(Component)]
+pub struct Person {
+    frame_index: usize,
+    pub state: PersonState,
+    /// Points patrolled in turn, only their x is used as people keep to the ground.
+    pub waypoints: Vec<Vec2>,
+    next_waypoint: usize,
+    pub speeds: PersonSpeeds,
+    /// Which way the person is looking, -1 for left and 1 for right.
+    pub facing: f32,
+    // seconds spent in the current state
+    state_time: f32,
+    // seconds since the crow was last seen
+    unseen_time: f32,
+    last_seen: Vec2,
+    // where the last noise worth investigating came from
+    heard: Vec2,
+    // where the person was when they first noticed the crow
+    post: Vec2,
+}
+
+impl Person {
+    pub fn new(position: Vec2, waypoints: Vec<Vec2>, speeds: PersonSpeeds) -> Self {
+        Self {
+            frame_index: 0,
+            state: PersonState::Idle,
+            waypoints,
+            next_waypoint: 0,
+            speeds,
+            facing: 1.0,
+            state_time: 0.0,
+            unseen_time: 0.0,
+            last_seen: position,
+            heard: position,
+            post: position,
+        }
+    }
+
+    /// Takes the person's patrol and post along when they are put somewhere else, so they do not
+    /// walk straight back.
+    pub(crate) fn move_by(&mut self, offset: Vec2) {
+        for waypoint in self.waypoints.iter_mut() {
+            *waypoint += offset;
+        }
+        self.post += offset;
+    }
+
+    fn set_state(&mut self, state: PersonState) {
+        self.state = state;
+        self.state_time = 0.0;
+    }
+
+    // Someone who is not already after the crow goes to look where a noise came from.
+    fn hear(&mut self, position: Vec2, source: Vec2) {
+        match self.state {
+            PersonState::Idle | PersonState::Patrol => self.post = position,
+            PersonState::Return | PersonState::Investigate => {}
+            PersonState::Suspicious | PersonState::Chase => return,
+        }
+        self.heard = source;
+        self.set_state(PersonState::Investigate);
+    }
+}
+
+pub(crate) struct PeopleSprites {
+    person: Handle<TextureAtlas>,
+}
+
+/// Spawns the people guarding the jewels. They patrol until they spot the crow, then chase it
+/// until they lose sight of it for long enough. Noises within earshot are investigated. People
+/// are kinematic bodies like the crow, they stop at walls and ledges and keep out of each
+/// other's way.
+pub struct PeoplePlugin;
+
+impl Plugin for PeoplePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_people_sprites)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(spawn_people))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(GameplayTick)
+                    .with_system(update_people.before(PhysicsSystem::Integrate))
+                    .with_system(
+                        separate_people
+                            .after(PhysicsSystem::Integrate)
+                            .before(PhysicsSystem::Resolve),
+                    )
+                    .with_system(hear_noises.after(NoiseSystem::Emit))
+                    .with_system(animate_people),
+            );
+    }
+}
+
+fn load_people_sprites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let person_handle = asset_server.load("walking_stickman.png");
+    let person_atlas = TextureAtlas::from_grid(person_handle, Vec2::new(80.0, 80.0), 4, 1);
+    commands.insert_resource(PeopleSprites {
+        person: texture_atlases.add(person_atlas),
+    });
+}
+
+fn spawn_people(
+    mut commands: Commands,
+    sprites: Res<PeopleSprites>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+) {
+    let Some(level) = levels.get(&current_level.0) else {
+        return;
+    };
+    let spawns = &level.people;
+    let mut rng = StdRng::seed_from_u64(spawns.seed);
+    for _ in 0..spawns.count {
+        let mut num: f32 = rng.gen_range(spawns.min_distance..=spawns.max_distance);
+        let sign: bool = rng.gen_bool(0.5);
+        if sign {
+            num *= -1.0;
+        }
+        let position = Vec2::new(level.crow_spawn.x + num, spawns.y);
+        let pace = rng.gen_range(0.8..1.2);
+        let speeds = PersonSpeeds {
+            walk: spawns.speeds.walk * pace,
+            chase: spawns.speeds.chase * pace,
+        };
+        let person = Person::new(position, patrol(position, spawns.patrol_distance), speeds);
+        spawn_person(&mut commands, &sprites, position, person);
+    }
+    for position in spawns.positions.iter() {
+        let waypoints = patrol(*position, spawns.patrol_distance);
+        let person = Person::new(*position, waypoints, spawns.speeds);
+        let person = spawn_person(&mut commands, &sprites, *position, person);
+        commands.entity(person).insert(LevelObject {
+            collider_type: ColliderType::Person,
+            position: *position,
+        });
+    }
+}
+
+fn patrol(position: Vec2, distance: f32) -> Vec<Vec2> {
+    if distance <= 0.0 {
+        return Vec::new();
+    }
+    vec![
+        position - Vec2::new(distance, 0.0),
+        position + Vec2::new(distance, 0.0),
+    ]
+}
+
+pub(crate) fn spawn_person(
+    commands: &mut Commands,
+    sprites: &PeopleSprites,
+    position: Vec2,
+    person: Person,
+) -> Entity {
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: sprites.person.clone(),
+            transform: Transform::from_translation(position.extend(1.0)),
+            ..Default::default()
+        })
+        .insert(Collider {
+            width: PERSON_SIZE.x,
+            height: PERSON_SIZE.y,
+            collider_type: ColliderType::Person,
+        })
+        .insert(person)
+        .insert(KinematicBody::new(PERSON_SIZE, 900.0, 600.0))
+        .insert(Contacts::default())
+        .insert(AnimationTimer(Timer::from_seconds(0.1, true)))
+        .insert(RunEntity)
+        .id()
+}
+
+fn animate_people(
+    time: Res<Time>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    mut people_query: Query<(
+        &mut Person,
+        &mut AnimationTimer,
+        &mut TextureAtlasSprite,
+        &Handle<TextureAtlas>,
+    )>,
+) {
+    for (mut person, mut timer, mut sprite, texture_atlas_handle) in people_query.iter_mut() {
+        timer.0.tick(time.delta());
+        if timer.0.just_finished() {
+            let texture_atlas = texture_atlases.get(texture_atlas_handle).unwrap();
+            sprite.index = (sprite.index + 1) % texture_atlas.textures.len();
+            person.frame_index = 0;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_people(
+    spatial_hash: Res<SpatialHash>,
+    mut people_query: Query<(
+        &mut Person,
+        &mut KinematicBody,
+        &Contacts,
+        &Transform,
+        &mut TextureAtlasSprite,
+    )>,
+    crow_query: Query<&Transform, (With<Crow>, Without<Person>)>,
+    collider_query: Query<(&Collider, &Transform), Without<Person>>,
+) {
+    let Ok(crow_transform) = crow_query.get_single() else {
+        return;
+    };
+    let crow = crow_transform.translation.truncate();
+    for (mut person, mut body, contacts, transform, mut sprite) in people_query.iter_mut() {
+        let position = transform.translation.truncate();
+        let sees_crow = sees(&person, position, crow, &spatial_hash, &collider_query);
+        person.state_time += TIME_STEP;
+        if sees_crow {
+            person.last_seen = crow;
+            person.unseen_time = 0.0;
+        } else {
+            person.unseen_time += TIME_STEP;
+        }
+
+        let target = match person.state {
+            PersonState::Idle | PersonState::Patrol if sees_crow => {
+                person.post = position;
+                person.set_state(PersonState::Suspicious);
+                None
+            }
+            PersonState::Idle => {
+                if !person.waypoints.is_empty() && person.state_time >= IDLE_TIME {
+                    person.set_state(PersonState::Patrol);
+                }
+                None
+            }
+            PersonState::Patrol => {
+                let waypoint = person.waypoints[person.next_waypoint];
+                if (waypoint.x - position.x).abs() <= ARRIVE_DISTANCE {
+                    person.next_waypoint = (person.next_waypoint + 1) % person.waypoints.len();
+                    person.set_state(PersonState::Idle);
+                    None
+                } else {
+                    Some((waypoint, person.speeds.walk))
+                }
+            }
+            PersonState::Suspicious => {
+                // a person only gives chase after getting a good look
+                if person.unseen_time >= SUSPICION_TIME {
+                    person.set_state(PersonState::Return);
+                } else if sees_crow && person.state_time >= SUSPICION_TIME {
+                    person.set_state(PersonState::Chase);
+                }
+                person.facing = (person.last_seen.x - position.x).signum();
+                None
+            }
+            PersonState::Chase => {
+                if person.unseen_time >= GIVE_UP_TIME {
+                    person.set_state(PersonState::Return);
+                    None
+                } else {
+                    Some((person.last_seen, person.speeds.chase))
+                }
+            }
+            PersonState::Investigate if sees_crow => {
+                person.set_state(PersonState::Suspicious);
+                None
+            }
+            PersonState::Investigate => {
+                if (person.heard.x - position.x).abs() <= ARRIVE_DISTANCE {
+                    // have a good look around before going back
+                    person.last_seen = person.heard;
+                    person.unseen_time = 0.0;
+                    person.set_state(PersonState::Suspicious);
+                    None
+                } else {
+                    Some((person.heard, person.speeds.walk))
+                }
+            }
+            PersonState::Return if sees_crow => {
+                person.set_state(PersonState::Suspicious);
+                None
+            }
+            PersonState::Return => {
+                if (person.post.x - position.x).abs() <= ARRIVE_DISTANCE {
+                    person.set_state(PersonState::Idle);
+                    None
+                } else {
+                    Some((person.post, person.speeds.walk))
+                }
+            }
+        };
+
+        body.velocity.x = 0.0;
+        if let Some((target, speed)) = target {
+            let offset = target.x - position.x;
+            if offset != 0.0 {
+                person.facing = offset.signum();
+            }
+            let direction = offset.signum();
+            let blocked = (direction < 0.0 && contacts.wall_left)
+                || (direction > 0.0 && contacts.wall_right)
+                || (contacts.grounded
+                    && !ground_ahead(
+                        position,
+                        body.size,
+                        direction,
+                        &spatial_hash,
+                        &collider_query,
+                    ));
+            if !blocked {
+                // never overshoot the target
+                body.velocity.x = (offset / TIME_STEP).clamp(-speed, speed);
+            } else if person.state == PersonState::Patrol {
+                // turn around and patrol the other way
+                person.next_waypoint = (person.next_waypoint + 1) % person.waypoints.len();
+                person.set_state(PersonState::Idle);
+            } else if person.state == PersonState::Return {
+                // this is as close as they get, they stay here from now on
+                person.post = position;
+                person.set_state(PersonState::Idle);
+            } else if person.state == PersonState::Investigate {
+                person.set_state(PersonState::Return);
+            }
+            // people chasing the crow wait at the wall or ledge for it to come back
+        }
+        sprite.flip_x = person.facing < 0.0;
+    }
+}
+
+// Whether there is something to stand on just past the side of the body the person walks
+// towards.
+fn ground_ahead(
+    position: Vec2,
+    size: Vec2,
+    direction: f32,
+    spatial_hash: &SpatialHash,
+    collider_query: &Query<(&Collider, &Transform), Without<Person>>,
+) -> bool {
+    let probe = position
+        + Vec2::new(
+            direction * (size.x / 2.0 + LEDGE_PROBE),
+            -(size.y / 2.0 + LEDGE_PROBE),
+        );
+    spatial_hash
+        .query(probe, Vec2::ZERO)
+        .into_iter()
+        .filter_map(|entity| collider_query.get(entity).ok())
+        .filter(|(collider, _)| collider.collider_type == ColliderType::Surface)
+        .any(|(collider, transform)| {
+            let distance = (probe - transform.translation.truncate()).abs();
+            distance.x <= collider.width / 2.0 && distance.y <= collider.height / 2.0
+        })
+}
+
+fn hear_noises(
+    mut noise_events: EventReader<Noise>,
+    mut people_query: Query<(&mut Person, &Transform)>,
+) {
+    for noise in noise_events.iter() {
+        for (mut person, transform) in people_query.iter_mut() {
+            let position = transform.translation.truncate();
+            if position.distance(noise.position) <= noise.radius {
+                person.hear(position, noise.position);
+            }
+        }
+    }
+}
+
+// Pushes overlapping people apart sideways, half the overlap each. This runs before collisions
+// are resolved, so a person pushed into a wall is moved back out of it by the wall.
+fn separate_people(
+    spatial_hash: Res<SpatialHash>,
+    mut people_query: Query<(Entity, &KinematicBody, &mut Transform), With<Person>>,
+) {
+    let people: HashMap<Entity, (Vec2, Vec2)> = people_query
+        .iter()
+        .map(|(entity, body, transform)| (entity, (transform.translation.truncate(), body.size)))
+        .collect();
+    for (entity, (position, size)) in people.iter() {
+        let mut push = 0.0;
+        // the spatial hash still has people where they were before this tick's movement
+        for other in spatial_hash.query(*position, *size * 2.0) {
+            let Some((other_position, other_size)) = people.get(&other) else {
+                continue;
+            };
+            if other == *entity {
+                continue;
+            }
+            let overlap = (*size + *other_size) / 2.0 - (*position - *other_position).abs();
+            if overlap.x <= 0.0 || overlap.y <= 0.0 {
+                continue;
+            }
+            // people standing exactly on top of each other split up by spawn order
+            let away = if position.x == other_position.x {
+                if *entity < other {
+                    -1.0
+                } else {
+                    1.0
+                }
+            } else {
+                (position.x - other_position.x).signum()
+            };
+            push += overlap.x / 2.0 * away;
+        }
+        if push != 0.0 {
+            if let Ok((_, _, mut transform)) = people_query.get_mut(*entity) {
+                transform.translation.x += push;
+            }
+        }
+    }
+}
+
+// Whether a person standing at `position` can see the crow at `crow`: in front of them, not too
+// far away or high up, and with no surface in between.
+fn sees(
+    person: &Person,
+    position: Vec2,
+    crow: Vec2,
+    spatial_hash: &SpatialHash,
+    collider_query: &Query<(&Collider, &Transform), Without<Person>>,
+) -> bool {
+    let offset = crow - position;
+    if offset.x.abs() > SIGHT_RANGE || offset.y > SIGHT_HEIGHT {
+        return false;
+    }
+    // while chasing, a person keeps an eye on the crow whichever way it goes
+    if person.state != PersonState::Chase && offset.x * person.facing < 0.0 {
+        return false;
+    }
+    let blocked = spatial_hash
+        .query(position + offset / 2.0, offset.abs())
+        .into_iter()
+        .filter_map(|entity| collider_query.get(entity).ok())
+        .filter(|(collider, _)| collider.collider_type == ColliderType::Surface)
+        .any(|(collider, transform)| {
+            let size = Vec2::new(collider.width, collider.height);
+            sweep(
+                position,
+                Vec2::ZERO,
+                offset,
+                transform.translation.truncate(),
+                size,
+            )
+            .is_some()
+        });
+    !blocked
+}
@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+
+use crate::spatial::{update_spatial_hash, SpatialHash};
+use crate::state::{GameplayTick, TIME_STEP};
+use crate::world::{Collider, ColliderType};
+
+// bodies this close to a surface count as touching it, so that resting on the ground or
+// leaning against a wall does not flicker between contact and no contact every tick
+pub(crate) const CONTACT_SKIN: f32 = 0.5;
+
+// a body can slide along this many surfaces in a single tick
+const MAX_SWEEPS: usize = 3;
+
+/// Something that moves by its velocity and falls under gravity, one `TIME_STEP` at a time.
+/// It is pushed out of surfaces and its `Contacts` tell which sides it touches.
+#[derive(Component)]
+pub struct KinematicBody {
+    /// Width and height of the body's collision box.
+    pub size: Vec2,
+    /// In pixels per second.
+    pub velocity: Vec2,
+    /// Downward acceleration in pixels per second squared.
+    pub gravity: f32,
+    /// The fastest the body can fall, in pixels per second.
+    pub terminal_velocity: f32,
+}
+
+impl KinematicBody {
+    pub fn new(size: Vec2, gravity: f32, terminal_velocity: f32) -> Self {
+        Self {
+            size,
+            velocity: Vec2::ZERO,
+            gravity,
+            terminal_velocity,
+        }
+    }
+}
+
+/// The sides of a `KinematicBody` that touched a surface in the last tick.
+#[derive(Component, Default, Clone, Copy, Debug, PartialEq)]
+pub struct Contacts {
+    pub grounded: bool,
+    pub wall_left: bool,
+    pub wall_right: bool,
+    pub ceiling: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum PhysicsSystem {
+    /// Velocities are turned into movement. Systems steering bodies run before it.
+    Integrate,
+    /// Bodies are pushed out of surfaces and their `Contacts` are updated. Systems reacting to
+    /// where bodies ended up run after it.
+    Resolve,
+    /// The `SpatialHash` is brought up to date with where bodies ended up. Systems querying it
+    /// for other bodies run after it.
+    Broadphase,
+}
+
+/// Moves every `KinematicBody` on the gameplay tick and keeps it out of the level's surfaces.
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialHash>()
+            .add_system_to_stage(CoreStage::PostUpdate, update_spatial_hash)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(GameplayTick)
+                    .with_system(integrate_bodies.label(PhysicsSystem::Integrate))
+                    .with_system(
+                        resolve_collisions
+                            .label(PhysicsSystem::Resolve)
+                            .after(PhysicsSystem::Integrate),
+                    )
+                    .with_system(
+                        update_spatial_hash
+                            .label(PhysicsSystem::Broadphase)
+                            .after(PhysicsSystem::Resolve),
+                    ),
+            );
+    }
+}
+
+fn integrate_bodies(
+    spatial_hash: Res<SpatialHash>,
+    mut body_query: Query<(&mut KinematicBody, &mut Transform, Option<&Contacts>)>,
+    collider_query: Query<(&Collider, &Transform), Without<KinematicBody>>,
+) {
+    for (mut body, mut transform, contacts) in body_query.iter_mut() {
+        let grounded = contacts.is_some_and(|contacts| contacts.grounded);
+        if grounded && body.velocity.y <= 0.0 {
+            body.velocity.y = 0.0;
+        } else {
+            body.velocity.y =
+                (body.velocity.y - body.gravity * TIME_STEP).max(-body.terminal_velocity);
+        }
+
+        // move along the velocity until the first surface in the way, then slide along that
+        // surface with whatever movement is left, so that fast bodies cannot skip past thin ones
+        let mut position = transform.translation.truncate();
+        let mut movement = body.velocity * TIME_STEP;
+        let surfaces = surfaces(
+            &spatial_hash,
+            &collider_query,
+            position + movement / 2.0,
+            body.size + movement.abs(),
+        );
+        for _ in 0..MAX_SWEEPS {
+            let hit = surfaces
+                .iter()
+                .filter_map(|(center, size)| sweep(position, body.size, movement, *center, *size))
+                .min_by(|a, b| a.time.total_cmp(&b.time));
+            let Some(hit) = hit else {
+                position += movement;
+                break;
+            };
+            position += movement * hit.time;
+            movement *= 1.0 - hit.time;
+            if hit.normal.x != 0.0 {
+                movement.x = 0.0;
+                body.velocity.x = 0.0;
+            } else {
+                movement.y = 0.0;
+                body.velocity.y = 0.0;
+            }
+        }
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+/// Where a box moving by `movement` first touches another box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepHit {
+    /// How far along `movement` the boxes meet, from 0 to 1.
+    pub time: f32,
+    /// Which way the other box's side faces, straight along one axis.
+    pub normal: Vec2,
+}
+
+/// Sweeps the box at `position` along `movement` against the box at `other`. Boxes that
+/// already overlap, or only slide along each other's sides, are not hits; the push out in
+/// `PhysicsSystem::Resolve` deals with those.
+pub fn sweep(
+    position: Vec2,
+    size: Vec2,
+    movement: Vec2,
+    other: Vec2,
+    other_size: Vec2,
+) -> Option<SweepHit> {
+    let reach = (size + other_size) / 2.0;
+    let offset = other - position;
+    let mut entry = [f32::NEG_INFINITY; 2];
+    let mut exit = [f32::INFINITY; 2];
+    for axis in 0..2 {
+        if movement[axis] == 0.0 {
+            if offset[axis].abs() >= reach[axis] {
+                return None;
+            }
+        } else {
+            let direction = movement[axis].signum();
+            entry[axis] = (offset[axis] - direction * reach[axis]) / movement[axis];
+            exit[axis] = (offset[axis] + direction * reach[axis]) / movement[axis];
+        }
+    }
+    let time = entry[0].max(entry[1]);
+    if !(0.0..1.0).contains(&time) || time >= exit[0].min(exit[1]) {
+        return None;
+    }
+    let normal = if entry[0] > entry[1] {
+        Vec2::new(-movement.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, -movement.y.signum())
+    };
+    Some(SweepHit { time, normal })
+}
+
+fn resolve_collisions(
+    spatial_hash: Res<SpatialHash>,
+    mut body_query: Query<(&mut KinematicBody, &mut Transform, &mut Contacts)>,
+    collider_query: Query<(&Collider, &Transform), Without<KinematicBody>>,
+) {
+    for (mut body, mut transform, mut contacts) in body_query.iter_mut() {
+        let mut position = transform.translation.truncate();
+        let mut velocity = body.velocity;
+        let mut surfaces: Vec<(Vec2, Vec2)> = surfaces(
+            &spatial_hash,
+            &collider_query,
+            position,
+            body.size + Vec2::splat(CONTACT_SKIN * 2.0),
+        )
+        .into_iter()
+        .filter(|(center, size)| {
+            let overlap = overlap(position, body.size, *center, *size);
+            overlap.x > -CONTACT_SKIN && overlap.y > -CONTACT_SKIN
+        })
+        .collect();
+        // the deepest contact goes first, so a body sliding over the seam between two floor
+        // tiles is lifted out of both instead of catching on the edge of the next one
+        surfaces.sort_by(|(a, a_size), (b, b_size)| {
+            let a = overlap(position, body.size, *a, *a_size);
+            let b = overlap(position, body.size, *b, *b_size);
+            (b.x * b.y).total_cmp(&(a.x * a.y))
+        });
+
+        *contacts = Contacts::default();
+        for (center, size) in surfaces {
+            let overlap = overlap(position, body.size, center, size);
+            if overlap.x <= -CONTACT_SKIN || overlap.y <= -CONTACT_SKIN {
+                // an earlier push already moved the body clear of this one
+                continue;
+            }
+            if overlap.x <= 0.0 && overlap.y <= 0.0 {
+                // only the corners touch, which is neither a wall nor a floor
+                continue;
+            }
+            let away = (position - center).signum();
+            // push out along the axis that needs the shortest move, a body that is only
+            // touching a side is left where it is
+            if overlap.x < overlap.y {
+                if overlap.x > 0.0 {
+                    position.x += overlap.x * away.x;
+                }
+                if away.x > 0.0 {
+                    contacts.wall_left = true;
+                    velocity.x = velocity.x.max(0.0);
+                } else {
+                    contacts.wall_right = true;
+                    velocity.x = velocity.x.min(0.0);
+                }
+            } else {
+                if overlap.y > 0.0 {
+                    position.y += overlap.y * away.y;
+                }
+                if away.y > 0.0 {
+                    contacts.grounded = true;
+                    velocity.y = velocity.y.max(0.0);
+                } else {
+                    contacts.ceiling = true;
+                    velocity.y = velocity.y.min(0.0);
+                }
+            }
+        }
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        body.velocity = velocity;
+    }
+}
+
+// Centers and sizes of the surfaces around the box at `center`.
+fn surfaces(
+    spatial_hash: &SpatialHash,
+    collider_query: &Query<(&Collider, &Transform), Without<KinematicBody>>,
+    center: Vec2,
+    size: Vec2,
+) -> Vec<(Vec2, Vec2)> {
+    spatial_hash
+        .query(center, size)
+        .into_iter()
+        .filter_map(|entity| collider_query.get(entity).ok())
+        .filter(|(collider, _)| collider.collider_type == ColliderType::Surface)
+        .map(|(collider, transform)| {
+            (
+                transform.translation.truncate(),
+                Vec2::new(collider.width, collider.height),
+            )
+        })
+        .collect()
+}
+
+// How far two boxes reach into each other along each axis, negative when they are apart.
+fn overlap(a: Vec2, a_size: Vec2, b: Vec2, b_size: Vec2) -> Vec2 {
+    (a_size + b_size) / 2.0 - (a - b).abs()
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::world::Collider;
+
+// a bit bigger than the crow, people and jewels, so that most of them take up one to four cells
+const CELL_SIZE: f32 = 128.0;
+
+/// Broad phase for collisions: every `Collider` filed under the grid cells its box covers.
+///
+/// The hash is brought up to date after every gameplay tick's `PhysicsSystem::Resolve`, and once
+/// a frame after `CoreStage::Update` for colliders spawned, moved or despawned outside of the
+/// gameplay tick, which are found from the next frame on. It only narrows down candidates, the
+/// colliders' own transforms still decide what actually touches.
+#[derive(Default)]
+pub struct SpatialHash {
+    cells: HashMap<IVec2, Vec<Entity>>,
+    // the first and last cell covered by each entity, to find it again when it moves
+    entries: HashMap<Entity, (IVec2, IVec2)>,
+}
+
+impl SpatialHash {
+    /// Files `entity` under the cells covered by the box at `center`, replacing wherever it
+    /// was before.
+    pub fn insert(&mut self, entity: Entity, center: Vec2, size: Vec2) {
+        let range = cell_range(center, size);
+        if self.entries.get(&entity) == Some(&range) {
+            return;
+        }
+        self.remove(entity);
+        for cell in cells(range) {
+            self.cells.entry(cell).or_default().push(entity);
+        }
+        self.entries.insert(entity, range);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let Some(range) = self.entries.remove(&entity) else {
+            return;
+        };
+        for cell in cells(range) {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|other| *other != entity);
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Every entity filed under a cell the box at `center` covers, each of them once.
+    pub fn query(&self, center: Vec2, size: Vec2) -> Vec<Entity> {
+        let mut found: Vec<Entity> = cells(cell_range(center, size))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn cell_range(center: Vec2, size: Vec2) -> (IVec2, IVec2) {
+    let min = ((center - size / 2.0) / CELL_SIZE).floor();
+    let max = ((center + size / 2.0) / CELL_SIZE).floor();
+    (min.as_ivec2(), max.as_ivec2())
+}
+
+fn cells((min, max): (IVec2, IVec2)) -> impl Iterator<Item = IVec2> {
+    (min.x..=max.x).flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_spatial_hash(
+    mut spatial_hash: ResMut<SpatialHash>,
+    collider_query: Query<
+        (Entity, &Collider, &Transform),
+        Or<(Changed<Collider>, Changed<Transform>)>,
+    >,
+    removed: RemovedComponents<Collider>,
+) {
+    for entity in removed.iter() {
+        spatial_hash.remove(entity);
+    }
+    for (entity, collider, transform) in collider_query.iter() {
+        spatial_hash.insert(
+            entity,
+            transform.translation.truncate(),
+            Vec2::new(collider.width, collider.height),
+        );
+    }
+}
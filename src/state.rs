@@ -0,0 +1,83 @@
+use bevy::core::FixedTimestep;
+use bevy::ecs::schedule::ShouldRun;
+use bevy::ecs::system::In;
+use bevy::prelude::*;
+
+use crate::input::{Action, Actions};
+
+pub const TIME_STEP: f32 = 1.0 / 60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    Loading,
+    MainMenu,
+    Playing,
+    Paused,
+    GameOver,
+    LevelComplete,
+    Editor,
+}
+
+/// How hard the game is, set before a run starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+/// Run criteria shared by every gameplay system that ticks at the fixed `TIME_STEP`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, RunCriteriaLabel)]
+pub struct GameplayTick;
+
+/// Owns the `GameState` machine and the fixed gameplay tick the other plugins schedule on.
+pub struct GameStatePlugin {
+    /// Tick the gameplay every `TIME_STEP` seconds of wall clock time. When disabled, every
+    /// `App::update` is exactly one gameplay tick, which is what headless simulations want.
+    pub fixed_timestep: bool,
+}
+
+impl Default for GameStatePlugin {
+    fn default() -> Self {
+        Self {
+            fixed_timestep: true,
+        }
+    }
+}
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        let gameplay_tick = if self.fixed_timestep {
+            FixedTimestep::step(TIME_STEP as f64)
+                .chain(run_if_playing)
+                .label(GameplayTick)
+        } else {
+            every_update.chain(run_if_playing).label(GameplayTick)
+        };
+        app.add_state(GameState::Loading)
+            .init_resource::<Difficulty>()
+            .add_system_set(SystemSet::new().with_run_criteria(gameplay_tick))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(pause_input));
+    }
+}
+
+fn every_update() -> ShouldRun {
+    ShouldRun::Yes
+}
+
+// Gates fixed-timestep sets so they only tick while a run is in progress.
+pub fn run_if_playing(In(input): In<ShouldRun>, state: Res<State<GameState>>) -> ShouldRun {
+    match state.current() {
+        GameState::Playing => input,
+        _ => ShouldRun::No,
+    }
+}
+
+// Pauses the run, the pause menu takes care of resuming it.
+fn pause_input(mut actions: ResMut<Actions>, mut state: ResMut<State<GameState>>) {
+    if actions.just_pressed(Action::Pause) && state.push(GameState::Paused).is_ok() {
+        // the same press must not be seen again by the state we just switched to
+        actions.reset_all();
+    }
+}
@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+
+use crate::level::{CurrentLevel, Decoration, Level, Surface};
+use crate::state::GameState;
+
+const NEST_SIZE: Vec2 = bevy::math::const_vec2!([96.0, 32.0]);
+
+#[derive(Component)]
+pub struct Collider {
+    pub width: f32,
+    pub height: f32,
+    pub collider_type: ColliderType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColliderType {
+    Surface,
+    Jewel,
+    Person,
+    /// Where the crow delivers the jewels it carries.
+    Nest,
+}
+
+#[derive(Component)]
+pub struct BirdCamera {}
+
+#[derive(Component)]
+pub struct Background();
+
+#[derive(Component)]
+pub struct AnimationTimer(pub Timer);
+
+/// Entities that belong to a single run and are despawned when the game restarts.
+#[derive(Component)]
+pub struct RunEntity;
+
+/// The texture a surface was built with, so that it can be written back to a level file.
+#[derive(Component)]
+pub struct SurfaceTexture(pub String);
+
+/// Entities built from the level file, rebuilt whenever the file changes on disk.
+#[derive(Component)]
+pub struct LevelGeometry;
+
+/// Something the level file placed, or the editor added to it, and where it goes back in the
+/// file. Saving a level writes out these and nothing else, so what a run spawned on its own stays
+/// out of the file and bodies that walked or were carried off are saved where they started.
+#[derive(Component)]
+pub struct LevelObject {
+    pub collider_type: ColliderType,
+    pub position: Vec2,
+}
+
+/// Sets up the cameras and builds the level's background and geometry at the start of every run.
+pub struct WorldPlugin;
+
+impl Plugin for WorldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_cameras)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(spawn_world))
+            .add_system_set(SystemSet::on_exit(GameState::GameOver).with_system(despawn_world))
+            .add_system(reload_level_geometry);
+    }
+}
+
+fn spawn_cameras(mut commands: Commands) {
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(BirdCamera {});
+    commands.spawn_bundle(UiCameraBundle::default());
+}
+
+fn spawn_world(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+) {
+    if let Some(level) = levels.get(&current_level.0) {
+        spawn_level_geometry(&mut commands, &asset_server, level);
+    }
+}
+
+fn reload_level_geometry(
+    mut commands: Commands,
+    mut level_events: EventReader<AssetEvent<Level>>,
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    geometry_query: Query<Entity, With<LevelGeometry>>,
+) {
+    for event in level_events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        // nothing to rebuild before the first run has spawned the level
+        if *handle != current_level.0 || geometry_query.is_empty() {
+            continue;
+        }
+        let Some(level) = levels.get(handle) else {
+            continue;
+        };
+        for entity in geometry_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        spawn_level_geometry(&mut commands, &asset_server, level);
+    }
+}
+
+fn spawn_level_geometry(commands: &mut Commands, asset_server: &AssetServer, level: &Level) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load(level.background.as_str()),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(800.0, 600.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(level.crow_spawn.extend(0.0)),
+            ..Default::default()
+        })
+        .insert(Background {})
+        .insert(LevelGeometry)
+        .insert(RunEntity);
+    for surface in level.surfaces.iter() {
+        spawn_surface(commands, asset_server, surface);
+    }
+    for position in level.nests.iter() {
+        spawn_nest(commands, *position);
+    }
+    for decoration in level.decorations.iter() {
+        match decoration {
+            Decoration::Sprite {
+                texture,
+                position,
+                size,
+            } => {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        texture: asset_server.load(texture.as_str()),
+                        transform: Transform::from_translation(position.extend(0.5)),
+                        sprite: Sprite {
+                            custom_size: Some(*size),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .insert(LevelGeometry)
+                    .insert(RunEntity);
+            }
+            Decoration::Text {
+                text,
+                position,
+                font_size,
+            } => {
+                commands
+                    .spawn_bundle(Text2dBundle {
+                        transform: Transform::from_translation(position.extend(1.0)),
+                        text: Text::with_section(
+                            text.clone(),
+                            TextStyle {
+                                font: asset_server.load("Inconsolata-Regular.ttf"),
+                                font_size: *font_size,
+                                color: Color::BLACK,
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    })
+                    .insert(LevelGeometry)
+                    .insert(RunEntity);
+            }
+        }
+    }
+}
+
+pub(crate) fn spawn_surface(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    surface: &Surface,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load(surface.texture.as_str()),
+            transform: Transform::from_translation(surface.position.extend(1.0)),
+            sprite: Sprite {
+                custom_size: Some(surface.size),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Collider {
+            width: surface.size.x,
+            height: surface.size.y,
+            collider_type: ColliderType::Surface,
+        })
+        .insert(SurfaceTexture(surface.texture.clone()))
+        .insert(LevelObject {
+            collider_type: ColliderType::Surface,
+            position: surface.position,
+        })
+        .insert(LevelGeometry)
+        .insert(RunEntity);
+}
+
+pub(crate) fn spawn_nest(commands: &mut Commands, position: Vec2) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform::from_translation(position.extend(0.5)),
+            sprite: Sprite {
+                color: Color::rgb(0.45, 0.3, 0.15),
+                custom_size: Some(NEST_SIZE),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Collider {
+            width: NEST_SIZE.x,
+            height: NEST_SIZE.y,
+            collider_type: ColliderType::Nest,
+        })
+        .insert(LevelObject {
+            collider_type: ColliderType::Nest,
+            position,
+        })
+        .insert(LevelGeometry)
+        .insert(RunEntity);
+}
+
+fn despawn_world(mut commands: Commands, run_query: Query<Entity, With<RunEntity>>) {
+    for entity in run_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
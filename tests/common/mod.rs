@@ -0,0 +1,153 @@
+// Shared by the integration tests, not every test file uses every helper.
+#![allow(dead_code)]
+
+use bevy::prelude::*;
+use crow_jewels::{
+    Collider, ColliderType, Contacts, Crow, CurrentLevel, GameState, HeadlessPlugins,
+    KinematicBody, Menu, Person, PersonSpeeds, ScriptedInput,
+};
+
+// Builds a headless app and runs it until the level has loaded and the main menu is open.
+pub fn menu_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugins);
+    for _ in 0..1000 {
+        app.update();
+        if game_state(&app) == GameState::MainMenu {
+            return app;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    panic!("the level never finished loading");
+}
+
+// Builds a headless app and runs it until the level has loaded and the run has started.
+pub fn headless_app() -> App {
+    let mut app = menu_app();
+    tap_key(&mut app, KeyCode::Return);
+    let mut query = app.world.query_filtered::<Entity, With<Crow>>();
+    assert!(
+        query.iter(&app.world).next().is_some(),
+        "the run did not start"
+    );
+    app
+}
+
+pub fn game_state(app: &App) -> GameState {
+    *app.world
+        .get_resource::<State<GameState>>()
+        .unwrap()
+        .current()
+}
+
+pub fn menu(app: &App) -> &Menu {
+    app.world.get_resource::<Menu>().unwrap()
+}
+
+// Presses `key` for a single update, with every other key released.
+pub fn tap_key(app: &mut App, key: KeyCode) {
+    let mut input = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    input.press(key);
+    app.update();
+    let mut input = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    *input = Input::default();
+}
+
+// The gamepad that tests press buttons and push sticks on.
+pub const GAMEPAD: Gamepad = Gamepad(0);
+
+// Presses `button_type` on `GAMEPAD` for a single update, with every other button released.
+pub fn tap_button(app: &mut App, button_type: GamepadButtonType) {
+    let mut input = app
+        .world
+        .get_resource_mut::<Input<GamepadButton>>()
+        .unwrap();
+    input.press(GamepadButton(GAMEPAD, button_type));
+    app.update();
+    let mut input = app
+        .world
+        .get_resource_mut::<Input<GamepadButton>>()
+        .unwrap();
+    *input = Input::default();
+}
+
+// Makes the level at `path` the current one and goes back to loading until it is ready.
+pub fn pick_level(app: &mut App, path: &str) {
+    let handle = app.world.get_resource::<AssetServer>().unwrap().load(path);
+    app.world.get_resource_mut::<CurrentLevel>().unwrap().0 = handle;
+    let mut state = app.world.get_resource_mut::<State<GameState>>().unwrap();
+    state.set(GameState::Loading).unwrap();
+}
+
+// Runs the app until it leaves `GameState::Loading`.
+pub fn wait_while_loading(app: &mut App) {
+    for _ in 0..1000 {
+        app.update();
+        if game_state(app) != GameState::Loading {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    panic!("the game never got out of loading");
+}
+
+pub fn run_ticks(app: &mut App, ticks: usize) {
+    for _ in 0..ticks {
+        app.update();
+    }
+}
+
+pub fn crow_translation(app: &mut App) -> Vec3 {
+    let mut query = app.world.query_filtered::<&Transform, With<Crow>>();
+    query.iter(&app.world).next().unwrap().translation
+}
+
+// Spawns a surface and runs a tick, the broad phase only picks new colliders up after the tick's
+// collisions have been resolved.
+pub fn spawn_surface(app: &mut App, position: Vec2, size: Vec2) {
+    app.world
+        .spawn()
+        .insert(Transform::from_translation(position.extend(1.0)))
+        .insert(Collider {
+            width: size.x,
+            height: size.y,
+            collider_type: ColliderType::Surface,
+        });
+    run_ticks(app, 1);
+}
+
+pub fn crow_velocity(app: &mut App) -> Vec2 {
+    let mut query = app.world.query_filtered::<&KinematicBody, With<Crow>>();
+    query.iter(&app.world).next().unwrap().velocity
+}
+
+pub fn script(app: &mut App) -> Mut<'_, ScriptedInput> {
+    app.world.get_resource_mut::<ScriptedInput>().unwrap()
+}
+
+// Clears out the level's people so that tests can place their own.
+pub fn app_without_people() -> App {
+    let mut app = headless_app();
+    let mut query = app.world.query_filtered::<Entity, With<Person>>();
+    for person in query.iter(&app.world).collect::<Vec<_>>() {
+        app.world.despawn(person);
+    }
+    run_ticks(&mut app, 120);
+    app
+}
+
+pub fn spawn_person(app: &mut App, position: Vec2, waypoints: Vec<Vec2>) -> Entity {
+    app.world
+        .spawn()
+        .insert(Transform::from_translation(position.extend(1.0)))
+        .insert(TextureAtlasSprite::default())
+        .insert(Collider {
+            width: 64.0,
+            height: 64.0,
+            collider_type: ColliderType::Person,
+        })
+        .insert(Person::new(position, waypoints, PersonSpeeds::default()))
+        .insert(KinematicBody::new(Vec2::new(64.0, 64.0), 900.0, 600.0))
+        .insert(Contacts::default())
+        .id()
+}
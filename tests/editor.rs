@@ -0,0 +1,162 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{game_state, menu_app, pick_level, run_ticks, tap_key, wait_while_loading};
+use crow_jewels::{Collider, ColliderType, EditorCursor, EditorPlugin, GameState, Jewel, Level};
+
+// A run of a copy of the shipped level, with the editor open over it. The copy has one person
+// standing far from the crow instead of the randomly placed ones.
+fn editor_app(dir: &std::path::Path) -> App {
+    std::fs::create_dir_all(dir).unwrap();
+    let shipped = std::fs::read_to_string("assets/levels/level1.level.ron").unwrap();
+    let path = dir.join("edited.level.ron");
+    std::fs::write(
+        &path,
+        shipped.replace("count: 10,", "count: 0, positions: [(-900.0, 20.0)],"),
+    )
+    .unwrap();
+
+    let mut app = menu_app();
+    app.add_plugin(EditorPlugin)
+        .init_resource::<Input<MouseButton>>();
+    pick_level(&mut app, path.to_str().unwrap());
+    wait_while_loading(&mut app);
+    tap_key(&mut app, KeyCode::Return);
+    assert_eq!(game_state(&app), GameState::Playing);
+    app
+}
+
+fn point_at(app: &mut App, x: f32, y: f32) {
+    app.world.get_resource_mut::<EditorCursor>().unwrap().0 = Some(Vec2::new(x, y));
+}
+
+fn click(app: &mut App, button: MouseButton) {
+    let mut input = app.world.get_resource_mut::<Input<MouseButton>>().unwrap();
+    input.press(button);
+    app.update();
+    let mut input = app.world.get_resource_mut::<Input<MouseButton>>().unwrap();
+    input.release(button);
+    app.update();
+    let mut input = app.world.get_resource_mut::<Input<MouseButton>>().unwrap();
+    *input = Input::default();
+}
+
+// Presses the left button at the cursor, moves it to `x`, `y` and lets go there.
+fn drag_to(app: &mut App, x: f32, y: f32) {
+    let mut input = app.world.get_resource_mut::<Input<MouseButton>>().unwrap();
+    input.press(MouseButton::Left);
+    app.update();
+    app.world
+        .get_resource_mut::<Input<MouseButton>>()
+        .unwrap()
+        .clear();
+    point_at(app, x, y);
+    app.update();
+    let mut input = app.world.get_resource_mut::<Input<MouseButton>>().unwrap();
+    input.release(MouseButton::Left);
+    app.update();
+    let mut input = app.world.get_resource_mut::<Input<MouseButton>>().unwrap();
+    *input = Input::default();
+}
+
+fn colliders_at(app: &mut App, collider_type: ColliderType) -> Vec<(Vec2, Vec2)> {
+    let mut query = app.world.query::<(&Collider, &Transform)>();
+    query
+        .iter(&app.world)
+        .filter(|(collider, _)| collider.collider_type == collider_type)
+        .map(|(collider, transform)| {
+            (
+                transform.translation.truncate(),
+                Vec2::new(collider.width, collider.height),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn edits_snap_to_the_grid_and_only_the_level_is_saved() {
+    let dir = std::env::temp_dir().join(format!("crow_jewels_editor_{}", std::process::id()));
+    let mut app = editor_app(&dir);
+    // long enough for the run to spawn a jewel of its own and the person to start patrolling
+    for _ in 0..900 {
+        run_ticks(&mut app, 1);
+        let mut query = app.world.query::<&Jewel>();
+        if query.iter(&app.world).count() > 1 {
+            break;
+        }
+    }
+    let mut query = app.world.query::<&Jewel>();
+    assert!(query.iter(&app.world).count() > 1);
+
+    tap_key(&mut app, KeyCode::Tab);
+    assert_eq!(game_state(&app), GameState::Editor);
+
+    // a surface lands on the grid point closest to the click
+    point_at(&mut app, -1003.0, 405.0);
+    click(&mut app, MouseButton::Left);
+    let surface = (Vec2::new(-1008.0, 400.0), Vec2::new(64.0, 64.0));
+    assert!(colliders_at(&mut app, ColliderType::Surface).contains(&surface));
+
+    // shift dragging its top right corner keeps the bottom left one in place
+    let mut keys = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    keys.press(KeyCode::LShift);
+    point_at(&mut app, -980.0, 420.0);
+    drag_to(&mut app, -940.0, 450.0);
+    let mut keys = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    *keys = Input::default();
+    let surface = (Vec2::new(-992.0, 408.0), Vec2::new(96.0, 80.0));
+    assert!(colliders_at(&mut app, ColliderType::Surface).contains(&surface));
+
+    // jewels can be placed and dragged around, but not resized
+    tap_key(&mut app, KeyCode::Key2);
+    point_at(&mut app, -1101.0, 499.0);
+    click(&mut app, MouseButton::Left);
+    drag_to(&mut app, -1150.0, 510.0);
+    let jewels = colliders_at(&mut app, ColliderType::Jewel);
+    let moved = jewels
+        .iter()
+        .find(|(position, _)| *position == Vec2::new(-1152.0, 512.0))
+        .copied()
+        .unwrap();
+    let mut keys = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    keys.press(KeyCode::LShift);
+    point_at(&mut app, -1140.0, 520.0);
+    drag_to(&mut app, -1100.0, 560.0);
+    let mut keys = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    *keys = Input::default();
+    assert!(colliders_at(&mut app, ColliderType::Jewel).contains(&moved));
+
+    // a nest placed and deleted again leaves nothing behind
+    tap_key(&mut app, KeyCode::Key4);
+    point_at(&mut app, -1300.0, 600.0);
+    click(&mut app, MouseButton::Left);
+    assert_eq!(colliders_at(&mut app, ColliderType::Nest).len(), 2);
+    click(&mut app, MouseButton::Right);
+    assert_eq!(colliders_at(&mut app, ColliderType::Nest).len(), 1);
+
+    let mut keys = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    keys.press(KeyCode::LControl);
+    keys.press(KeyCode::S);
+    app.update();
+    let mut keys = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    *keys = Input::default();
+
+    let saved = std::fs::read_to_string(dir.join("edited.level.ron")).unwrap();
+    let saved: Level = ron::de::from_str(&saved).unwrap();
+    assert_eq!(saved.surfaces.len(), 9);
+    assert!(saved.surfaces.iter().any(|surface| {
+        surface.texture == "brick.png"
+            && surface.position == Vec2::new(-992.0, 408.0)
+            && surface.size == Vec2::new(96.0, 80.0)
+    }));
+    // the jewel the run spawned is not part of the level
+    assert_eq!(
+        saved.jewels,
+        vec![Vec2::new(-150.0, 20.0), Vec2::new(-1152.0, 512.0)]
+    );
+    assert_eq!(saved.nests, vec![Vec2::new(700.0, 116.0)]);
+    // people are saved where they were placed, not where their patrol took them
+    assert_eq!(saved.people.count, 0);
+    assert_eq!(saved.people.positions, vec![Vec2::new(-900.0, 20.0)]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use bevy::app::Events;
+use bevy::prelude::*;
+use common::{
+    crow_translation, crow_velocity, game_state, headless_app, menu_app, run_ticks, tap_button,
+    GAMEPAD,
+};
+use crow_jewels::{
+    Collider, ColliderType, Crow, GameState, Health, HighScores, Menu, MenuItem, PlayerInputPlugin,
+    StickSettings,
+};
+
+fn connect_gamepad(app: &mut App) {
+    let mut events = app
+        .world
+        .get_resource_mut::<Events<GamepadEvent>>()
+        .unwrap();
+    events.send(GamepadEvent(GAMEPAD, GamepadEventType::Connected));
+    app.update();
+}
+
+fn push_stick(app: &mut App, x: f32, y: f32) {
+    let mut axes = app.world.get_resource_mut::<Axis<GamepadAxis>>().unwrap();
+    axes.set(GamepadAxis(GAMEPAD, GamepadAxisType::LeftStickX), x);
+    axes.set(GamepadAxis(GAMEPAD, GamepadAxisType::LeftStickY), y);
+}
+
+// A run that is played from the gamepad instead of `ScriptedInput`.
+fn player_app() -> App {
+    let mut app = headless_app();
+    app.add_plugin(PlayerInputPlugin);
+    connect_gamepad(&mut app);
+    app
+}
+
+#[test]
+fn sticks_have_a_dead_zone_and_saturate() {
+    let settings = StickSettings::default();
+    assert_eq!(settings.apply(0.0), 0.0);
+    assert_eq!(settings.apply(0.15), 0.0);
+    assert_eq!(settings.apply(-0.2), 0.0);
+    assert!((settings.apply(0.55) - 0.5).abs() < 1e-5);
+    assert!((settings.apply(-0.55) + 0.5).abs() < 1e-5);
+    assert_eq!(settings.apply(0.95), 1.0);
+    assert_eq!(settings.apply(-1.0), -1.0);
+}
+
+#[test]
+fn sticks_without_room_to_saturate_are_all_or_nothing() {
+    let settings = StickSettings {
+        dead_zone: 0.5,
+        saturation: 0.5,
+    };
+    assert_eq!(settings.apply(0.5), 0.0);
+    assert_eq!(settings.apply(0.51), 1.0);
+    assert_eq!(settings.apply(-0.8), -1.0);
+    let settings = StickSettings {
+        dead_zone: 0.6,
+        saturation: 0.3,
+    };
+    assert_eq!(settings.apply(0.4), 0.0);
+    assert_eq!(settings.apply(-0.7), -1.0);
+}
+
+#[test]
+fn the_stick_walks_the_crow_as_fast_as_it_is_pushed() {
+    let mut app = player_app();
+    run_ticks(&mut app, 120);
+
+    // a stick resting slightly off center does nothing
+    push_stick(&mut app, 0.1, 0.0);
+    run_ticks(&mut app, 30);
+    assert_eq!(crow_velocity(&mut app).x, 0.0);
+
+    push_stick(&mut app, 0.55, 0.0);
+    run_ticks(&mut app, 30);
+    assert!((crow_velocity(&mut app).x - 100.0).abs() < 1.0);
+
+    push_stick(&mut app, -1.0, 0.0);
+    run_ticks(&mut app, 30);
+    assert!((crow_velocity(&mut app).x + 200.0).abs() < 1.0);
+}
+
+#[test]
+fn the_south_button_flaps() {
+    let mut app = player_app();
+    run_ticks(&mut app, 120);
+    tap_button(&mut app, GamepadButtonType::South);
+    assert!(crow_velocity(&mut app).y > 0.0);
+}
+
+#[test]
+fn the_stick_moves_through_the_menu_once_per_push() {
+    let mut app = menu_app();
+    connect_gamepad(&mut app);
+    push_stick(&mut app, 0.0, -1.0);
+    run_ticks(&mut app, 10);
+    let menu = app.world.get_resource::<Menu>().unwrap();
+    assert_eq!(menu.selected_item(), &MenuItem::LevelSelect);
+
+    push_stick(&mut app, 0.0, 0.0);
+    run_ticks(&mut app, 1);
+    push_stick(&mut app, 0.0, 1.0);
+    run_ticks(&mut app, 10);
+    let menu = app.world.get_resource::<Menu>().unwrap();
+    assert_eq!(menu.selected_item(), &MenuItem::Play);
+}
+
+#[test]
+fn a_gamepad_can_save_a_high_score_and_restart() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let mut query = app.world.query::<(&mut Crow, &mut Health)>();
+    let (mut crow, mut health) = query.iter_mut(&mut app.world).next().unwrap();
+    crow.score = 3;
+    health.lives = 1;
+    let translation = crow_translation(&mut app);
+    app.world
+        .spawn()
+        .insert(Transform::from_translation(
+            translation + Vec3::new(50.0, 0.0, 0.0),
+        ))
+        .insert(Collider {
+            width: 64.0,
+            height: 64.0,
+            collider_type: ColliderType::Person,
+        });
+    run_ticks(&mut app, 2);
+    assert_eq!(game_state(&app), GameState::GameOver);
+
+    // the press that saves the high score does not also restart
+    tap_button(&mut app, GamepadButtonType::South);
+    assert_eq!(game_state(&app), GameState::GameOver);
+    let high_scores = app.world.get_resource::<HighScores>().unwrap();
+    assert_eq!(high_scores.entries[0].name, "Crow");
+
+    tap_button(&mut app, GamepadButtonType::South);
+    assert_eq!(game_state(&app), GameState::Playing);
+}
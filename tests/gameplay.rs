@@ -0,0 +1,604 @@
+mod common;
+
+use std::collections::HashMap;
+
+use bevy::app::{Events, ManualEventReader};
+use bevy::prelude::*;
+use common::{crow_translation, crow_velocity, game_state, headless_app, run_ticks, script};
+use crow_jewels::{
+    pick_jewel_kind, spawn_interval, Carried, Collider, ColliderType, Crow, CrowControls,
+    CrowFlapped, CrowFlight, CrowState, CurrentLevel, Difficulty, GameState, Health, Jewel,
+    JewelKind, KinematicBody, Level, Person, RunEntity, Stamina, Surface, TIME_STEP,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn crow(app: &mut App) -> &Crow {
+    let mut query = app.world.query::<&Crow>();
+    query.iter(&app.world).next().unwrap()
+}
+
+fn crow_score(app: &mut App) -> usize {
+    crow(app).score
+}
+
+fn crow_health(app: &mut App) -> Mut<'_, Health> {
+    let mut query = app.world.query_filtered::<Entity, With<Crow>>();
+    let crow = query.iter(&app.world).next().unwrap();
+    app.world.get_mut::<Health>(crow).unwrap()
+}
+
+fn crow_stamina(app: &mut App) -> f32 {
+    let mut query = app.world.query_filtered::<&Stamina, With<Crow>>();
+    query.iter(&app.world).next().unwrap().current
+}
+
+// Runs `ticks` gameplay ticks and counts how many times the crow flapped during them.
+fn count_flaps(app: &mut App, ticks: usize) -> usize {
+    let mut reader = ManualEventReader::<CrowFlapped>::default();
+    let mut flaps = 0;
+    for _ in 0..ticks {
+        app.update();
+        let events = app.world.get_resource::<Events<CrowFlapped>>().unwrap();
+        flaps += reader.iter(events).count();
+    }
+    flaps
+}
+
+// Puts the crow high up in the air, with room to fall for a while.
+fn lift_crow(app: &mut App) {
+    let mut query = app.world.query_filtered::<&mut Transform, With<Crow>>();
+    query.iter_mut(&mut app.world).next().unwrap().translation.y += 400.0;
+}
+
+const FLAP: CrowControls = CrowControls {
+    left: false,
+    right: false,
+    flap: true,
+    dive: false,
+    stick: 0.0,
+};
+
+const DIVE: CrowControls = CrowControls {
+    left: false,
+    right: false,
+    flap: false,
+    dive: true,
+    stick: 0.0,
+};
+
+// Spawns a collider just far enough from the crow that their boxes overlap.
+fn spawn_next_to_crow(app: &mut App, collider_type: ColliderType) -> Entity {
+    let translation = crow_translation(app);
+    app.world
+        .spawn()
+        .insert(Transform::from_translation(
+            translation + Vec3::new(50.0, 0.0, 0.0),
+        ))
+        .insert(Collider {
+            width: 64.0,
+            height: 64.0,
+            collider_type,
+        })
+        .id()
+}
+
+#[test]
+fn crow_lands_on_the_big_brick() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+
+    // the 100x100 brick at (0, 50) has its top at y = 100, the crow is 60 high
+    let translation = crow_translation(&mut app);
+    assert_eq!(translation.x, 0.0);
+    assert!(
+        (translation.y - 130.0).abs() < 5.0,
+        "crow at {}",
+        translation
+    );
+
+    run_ticks(&mut app, 60);
+    assert_eq!(crow_translation(&mut app), translation);
+    let mut query = app.world.query::<&Crow>();
+    assert!(query.iter(&app.world).next().unwrap().crow_state == CrowState::Idle);
+}
+
+#[test]
+fn flapping_lifts_the_crow() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let grounded = crow_translation(&mut app);
+
+    script(&mut app).hold(
+        CrowControls {
+            flap: true,
+            ..Default::default()
+        },
+        10,
+    );
+    run_ticks(&mut app, 10);
+    assert!(crow_translation(&mut app).y > grounded.y + 50.0);
+}
+
+#[test]
+fn a_single_flap_rises_and_falls_back_down() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let grounded = crow_translation(&mut app);
+
+    script(&mut app).hold(
+        CrowControls {
+            flap: true,
+            ..Default::default()
+        },
+        1,
+    );
+    let mut highest = grounded.y;
+    let mut query = app.world.query_filtered::<&KinematicBody, With<Crow>>();
+    for _ in 0..120 {
+        run_ticks(&mut app, 1);
+        highest = highest.max(crow_translation(&mut app).y);
+        let body = query.iter(&app.world).next().unwrap();
+        assert!(body.velocity.y >= -body.terminal_velocity);
+    }
+    assert!(highest > grounded.y + 50.0);
+    assert!((crow_translation(&mut app).y - grounded.y).abs() < 1.0);
+}
+
+#[test]
+fn holding_flap_flaps_once_and_glides() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    lift_crow(&mut app);
+    script(&mut app).hold(FLAP, 60);
+    assert_eq!(count_flaps(&mut app, 60), 1);
+    let glide_fall_speed = CrowFlight::default().glide_fall_speed.unwrap();
+    let gravity_per_tick = 900.0 * TIME_STEP;
+    assert!(crow_velocity(&mut app).y >= -glide_fall_speed - gravity_per_tick);
+
+    // letting go falls at full speed again
+    run_ticks(&mut app, 20);
+    assert!(crow_velocity(&mut app).y < -glide_fall_speed - gravity_per_tick);
+}
+
+#[test]
+fn flaps_need_a_fresh_press_after_the_cooldown() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    // a second press 4 ticks later is still within the cooldown
+    script(&mut app)
+        .hold(FLAP, 2)
+        .wait(2)
+        .hold(FLAP, 2)
+        .wait(20)
+        .hold(FLAP, 2);
+    assert_eq!(count_flaps(&mut app, 6), 1);
+    assert_eq!(count_flaps(&mut app, 22), 1);
+}
+
+#[test]
+fn gliding_can_be_turned_off() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let mut query = app.world.query::<&mut CrowFlight>();
+    query
+        .iter_mut(&mut app.world)
+        .next()
+        .unwrap()
+        .glide_fall_speed = None;
+    lift_crow(&mut app);
+    script(&mut app).hold(FLAP, 60);
+    run_ticks(&mut app, 60);
+    assert!(crow_velocity(&mut app).y < -CrowFlight::default().glide_fall_speed.unwrap());
+}
+
+#[test]
+fn flying_tires_the_crow_until_it_perches() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let flight = CrowFlight::default();
+    let full = Stamina::default().max;
+    assert_eq!(crow_stamina(&mut app), full);
+
+    lift_crow(&mut app);
+    for _ in 0..10 {
+        script(&mut app).hold(FLAP, 1).wait(19);
+    }
+    let flaps = count_flaps(&mut app, 200);
+    // every flap and every moment in the air costs stamina, until there is not enough left
+    assert!(flaps > 1 && (flaps as f32) < full / flight.flap_stamina);
+    let tired = crow_stamina(&mut app);
+    assert!(tired < flight.flap_stamina);
+
+    // back on the ground the crow gets its breath back
+    run_ticks(&mut app, 120);
+    assert!(crow_stamina(&mut app) > tired);
+    run_ticks(&mut app, 180);
+    assert_eq!(crow_stamina(&mut app), full);
+    script(&mut app).hold(FLAP, 1);
+    assert_eq!(count_flaps(&mut app, 1), 1);
+}
+
+#[test]
+fn gliding_is_faster_sideways() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    lift_crow(&mut app);
+    script(&mut app).hold(
+        CrowControls {
+            right: true,
+            ..FLAP
+        },
+        60,
+    );
+    run_ticks(&mut app, 60);
+    assert_eq!(crow(&mut app).crow_state, CrowState::Glide);
+    assert!(crow_velocity(&mut app).x > CrowFlight::default().max_speed);
+
+    // letting go of flap folds the wings again
+    run_ticks(&mut app, 1);
+    assert_eq!(crow(&mut app).crow_state, CrowState::Fly);
+}
+
+#[test]
+fn diving_swoops_down_until_the_crow_lands() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let grounded = crow_translation(&mut app);
+
+    // diving does nothing on the ground
+    script(&mut app).hold(DIVE, 1);
+    run_ticks(&mut app, 1);
+    assert_eq!(crow(&mut app).crow_state, CrowState::Idle);
+
+    // the crow only notices it is off the ground after a tick
+    lift_crow(&mut app);
+    script(&mut app).hold(DIVE, 2);
+    run_ticks(&mut app, 2);
+    assert_eq!(crow(&mut app).crow_state, CrowState::Dive);
+    assert_eq!(crow_velocity(&mut app).y, -CrowFlight::default().dive_speed);
+
+    // 400 px at the dive speed takes 40 ticks, falling the same way would take 56
+    run_ticks(&mut app, 45);
+    assert!((crow_translation(&mut app).y - grounded.y).abs() < 1.0);
+    run_ticks(&mut app, 1);
+    assert_eq!(crow(&mut app).crow_state, CrowState::Idle);
+}
+
+#[test]
+fn a_dive_snatches_jewels_and_swoops_back_up() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let jewel = spawn_next_to_crow(&mut app, ColliderType::Jewel);
+    lift_crow(&mut app);
+    script(&mut app).hold(DIVE, 60);
+    let mut snatched = false;
+    for _ in 0..60 {
+        run_ticks(&mut app, 1);
+        if crow(&mut app).carrying.is_some() {
+            snatched = true;
+            break;
+        }
+    }
+    assert!(snatched);
+    assert_eq!(crow(&mut app).carrying, Some(jewel));
+    assert_eq!(crow(&mut app).crow_state, CrowState::Fly);
+    assert!(crow_velocity(&mut app).y > 0.0);
+}
+
+#[test]
+fn a_crow_caught_in_a_dive_is_knocked_back_up() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    spawn_next_to_crow(&mut app, ColliderType::Person);
+    lift_crow(&mut app);
+    let lives = crow_health(&mut app).lives;
+    script(&mut app).hold(DIVE, 60);
+    let mut caught = false;
+    for _ in 0..60 {
+        run_ticks(&mut app, 1);
+        if crow_health(&mut app).lives < lives {
+            caught = true;
+            break;
+        }
+    }
+    assert!(caught);
+    // the dive is still held, but the knockback is not overwritten by it
+    run_ticks(&mut app, 1);
+    assert_ne!(crow(&mut app).crow_state, CrowState::Dive);
+    assert!(crow_velocity(&mut app).y > 0.0);
+}
+
+#[test]
+fn walking_moves_the_crow_sideways() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let start = crow_translation(&mut app);
+
+    script(&mut app).hold(
+        CrowControls {
+            left: true,
+            ..Default::default()
+        },
+        30,
+    );
+    run_ticks(&mut app, 30);
+    assert!(crow_translation(&mut app).x < start.x);
+}
+
+#[test]
+fn touching_a_person_costs_a_life_and_knocks_the_crow_back() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    assert_eq!(crow_health(&mut app).lives, 3);
+
+    spawn_next_to_crow(&mut app, ColliderType::Person);
+    run_ticks(&mut app, 2);
+    assert_eq!(game_state(&app), GameState::Playing);
+    assert_eq!(crow_health(&mut app).lives, 2);
+    assert!(crow_health(&mut app).invulnerable > 0.0);
+
+    // the person was to the right, so the crow is sent up and to the left
+    let mut query = app.world.query_filtered::<&KinematicBody, With<Crow>>();
+    let velocity = query.iter(&app.world).next().unwrap().velocity;
+    assert!(
+        velocity.x < 0.0 && velocity.y > 0.0,
+        "velocity {}",
+        velocity
+    );
+
+    // a second person right away cannot catch the crow again
+    spawn_next_to_crow(&mut app, ColliderType::Person);
+    run_ticks(&mut app, 2);
+    assert_eq!(crow_health(&mut app).lives, 2);
+
+    // until the invulnerability wears off
+    run_ticks(&mut app, 120);
+    assert_eq!(crow_health(&mut app).invulnerable, 0.0);
+}
+
+#[test]
+fn losing_the_last_life_sets_game_over() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    crow_health(&mut app).lives = 1;
+
+    spawn_next_to_crow(&mut app, ColliderType::Person);
+    run_ticks(&mut app, 2);
+    assert_eq!(game_state(&app), GameState::GameOver);
+    assert_eq!(crow_health(&mut app).lives, 0);
+}
+
+#[test]
+fn touching_a_jewel_picks_it_up() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+
+    let jewel = spawn_next_to_crow(&mut app, ColliderType::Jewel);
+    run_ticks(&mut app, 2);
+    assert_eq!(crow_score(&mut app), 0);
+    assert_eq!(crow(&mut app).carrying, Some(jewel));
+    assert!(crow(&mut app).crow_state == CrowState::Pickup);
+    assert!(app.world.get::<Carried>(jewel).is_some());
+    assert!(app.world.get::<Collider>(jewel).is_none());
+
+    // the jewel goes wherever the crow goes
+    run_ticks(&mut app, 1);
+    let jewel_translation = app.world.get::<Transform>(jewel).unwrap().translation;
+    assert!(jewel_translation.distance(crow_translation(&mut app)) < 40.0);
+}
+
+#[test]
+fn delivering_a_jewel_to_a_nest_scores_it() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let jewel = spawn_next_to_crow(&mut app, ColliderType::Jewel);
+    app.world
+        .entity_mut(jewel)
+        .insert(Jewel::new(JewelKind::Crown));
+    run_ticks(&mut app, 2);
+
+    spawn_next_to_crow(&mut app, ColliderType::Nest);
+    run_ticks(&mut app, 2);
+    assert_eq!(crow_score(&mut app), JewelKind::Crown.value());
+    assert_eq!(crow(&mut app).carrying, None);
+    assert!(app.world.get_entity(jewel).is_none());
+}
+
+#[test]
+fn heavy_jewels_slow_the_crow_down() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let jewel = spawn_next_to_crow(&mut app, ColliderType::Jewel);
+    app.world
+        .entity_mut(jewel)
+        .insert(Jewel::new(JewelKind::Crown));
+    run_ticks(&mut app, 2);
+
+    let start = crow_translation(&mut app);
+    script(&mut app).hold(
+        CrowControls {
+            left: true,
+            ..Default::default()
+        },
+        60,
+    );
+    run_ticks(&mut app, 60);
+    let mut query = app.world.query_filtered::<&KinematicBody, With<Crow>>();
+    let speed = query.iter(&app.world).next().unwrap().velocity.x.abs();
+    assert!(crow_translation(&mut app).x < start.x);
+    assert!(
+        speed < CrowFlight::default().max_speed * 0.7,
+        "speed {}",
+        speed
+    );
+}
+
+#[test]
+fn common_jewels_spawn_more_often_than_rare_ones() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut counts = HashMap::new();
+    for _ in 0..10_000 {
+        *counts.entry(pick_jewel_kind(&mut rng)).or_insert(0) += 1;
+    }
+    for pair in JewelKind::ALL.windows(2) {
+        assert!(pair[0].rarity() > pair[1].rarity());
+        assert!(counts[&pair[0]] > counts[&pair[1]]);
+    }
+    assert!(counts[&JewelKind::Crown] > 0);
+}
+
+#[test]
+fn jewels_spawn_on_top_of_free_surfaces_up_to_the_cap() {
+    let mut app = headless_app();
+    let mut people = app.world.query_filtered::<Entity, With<Person>>();
+    for person in people.iter(&app.world).collect::<Vec<_>>() {
+        app.world.despawn(person);
+    }
+    app.world.insert_resource(Difficulty::Easy);
+    let handle = app.world.get_resource::<CurrentLevel>().unwrap().0.clone();
+    let mut levels = app.world.get_resource_mut::<Assets<Level>>().unwrap();
+    levels.get_mut(&handle).unwrap().jewel_spawns.max_jewels = 3;
+
+    // five spawns are due, the level already starts out with one jewel
+    let ticks = (spawn_interval(Difficulty::Easy) * 5.0 / TIME_STEP) as usize;
+    run_ticks(&mut app, ticks);
+
+    let mut query = app.world.query::<(&Collider, &Transform)>();
+    let colliders: Vec<(ColliderType, Vec2, Vec2)> = query
+        .iter(&app.world)
+        .map(|(collider, transform)| {
+            (
+                collider.collider_type,
+                transform.translation.truncate(),
+                Vec2::new(collider.width, collider.height),
+            )
+        })
+        .collect();
+    let surfaces: Vec<_> = colliders
+        .iter()
+        .filter(|(collider_type, _, _)| *collider_type == ColliderType::Surface)
+        .collect();
+    let jewels: Vec<_> = colliders
+        .iter()
+        .filter(|(collider_type, _, _)| *collider_type == ColliderType::Jewel)
+        .collect();
+    assert_eq!(jewels.len(), 3);
+    // the level's starting jewel is not spawned by the spawner
+    for (_, position, size) in jewels
+        .iter()
+        .filter(|(_, position, _)| position.x != -150.0)
+    {
+        let bottom = position.y - size.y / 2.0;
+        assert!(surfaces.iter().any(|(_, surface, surface_size)| {
+            (surface.y + surface_size.y / 2.0 - bottom).abs() < 0.01
+                && (surface.x - position.x).abs() <= (surface_size.x - size.x) / 2.0 + 0.01
+        }));
+        for (_, surface, surface_size) in surfaces.iter() {
+            let overlap = (*size + *surface_size) / 2.0 - (*position - *surface).abs();
+            assert!(overlap.x <= 0.01 || overlap.y <= 0.01);
+        }
+    }
+}
+
+#[test]
+fn jewels_only_spawn_fully_on_a_surface_with_room_for_the_crow() {
+    let mut app = headless_app();
+    let mut people = app.world.query_filtered::<Entity, With<Person>>();
+    for person in people.iter(&app.world).collect::<Vec<_>>() {
+        app.world.despawn(person);
+    }
+    app.world.insert_resource(Difficulty::Easy);
+    let handle = app.world.get_resource::<CurrentLevel>().unwrap().0.clone();
+    let mut query = app.world.query::<&Jewel>();
+    let jewels = query.iter(&app.world).count();
+    let ticks = (spawn_interval(Difficulty::Easy) * 3.0 / TIME_STEP) as usize;
+
+    // over the 32 wide brick at x = 100 every jewel would hang over an edge, and the floor
+    // around the brick is taken up by the brick itself
+    let mut levels = app.world.get_resource_mut::<Assets<Level>>().unwrap();
+    let level = levels.get_mut(&handle).unwrap();
+    level.jewel_spawns.min_x = 90.0;
+    level.jewel_spawns.max_x = 110.0;
+    run_ticks(&mut app, ticks);
+    assert_eq!(query.iter(&app.world).count(), jewels);
+
+    // on the floor under a ceiling that a coin would fit under but the crow would not, and
+    // that reaches above the spawn height so nothing lands on top of it
+    let mut levels = app.world.get_resource_mut::<Assets<Level>>().unwrap();
+    let level = levels.get_mut(&handle).unwrap();
+    level.jewel_spawns.min_x = -1310.0;
+    level.jewel_spawns.max_x = -1290.0;
+    level.surfaces.push(Surface {
+        texture: "brick.png".to_string(),
+        position: Vec2::new(-1300.0, 125.0),
+        size: Vec2::new(200.0, 170.0),
+    });
+    run_ticks(&mut app, ticks);
+    assert_eq!(query.iter(&app.world).count(), jewels);
+
+    // without the ceiling there is room
+    let mut levels = app.world.get_resource_mut::<Assets<Level>>().unwrap();
+    levels.get_mut(&handle).unwrap().surfaces.pop();
+    run_ticks(&mut app, ticks);
+    assert!(query.iter(&app.world).count() > jewels);
+}
+
+#[test]
+fn jewel_spawns_outside_the_level_bounds_spawn_nothing() {
+    let mut app = headless_app();
+    let handle = app.world.get_resource::<CurrentLevel>().unwrap().0.clone();
+    let mut levels = app.world.get_resource_mut::<Assets<Level>>().unwrap();
+    let level = levels.get_mut(&handle).unwrap();
+    level.jewel_spawns.min_x = level.bounds.max_x + 100.0;
+    level.jewel_spawns.max_x = level.bounds.max_x + 200.0;
+    let mut query = app.world.query::<&Jewel>();
+    let jewels = query.iter(&app.world).count();
+
+    let ticks = (spawn_interval(Difficulty::Normal) * 2.0 / TIME_STEP) as usize;
+    run_ticks(&mut app, ticks);
+    assert_eq!(query.iter(&app.world).count(), jewels);
+}
+
+#[test]
+fn getting_caught_while_carrying_drops_the_jewel() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let jewel = spawn_next_to_crow(&mut app, ColliderType::Jewel);
+    run_ticks(&mut app, 2);
+
+    spawn_next_to_crow(&mut app, ColliderType::Person);
+    run_ticks(&mut app, 2);
+    assert_eq!(game_state(&app), GameState::Playing);
+    assert_eq!(crow(&mut app).carrying, None);
+    assert!(app.world.get::<Carried>(jewel).is_none());
+    assert!(app.world.get::<Collider>(jewel).is_some());
+
+    // the crow does not grab it straight back while it is still shaken
+    run_ticks(&mut app, 10);
+    assert_eq!(crow(&mut app).carrying, None);
+    assert_eq!(crow_score(&mut app), 0);
+}
+
+#[test]
+fn restarting_rebuilds_the_initial_world() {
+    let mut app = headless_app();
+    let mut query = app.world.query_filtered::<Entity, With<RunEntity>>();
+    let initial_entities = query.iter(&app.world).count();
+
+    run_ticks(&mut app, 120);
+    crow_health(&mut app).lives = 1;
+    spawn_next_to_crow(&mut app, ColliderType::Person);
+    run_ticks(&mut app, 2);
+    assert_eq!(game_state(&app), GameState::GameOver);
+
+    app.world
+        .get_resource_mut::<Input<KeyCode>>()
+        .unwrap()
+        .press(KeyCode::Space);
+    run_ticks(&mut app, 1);
+    assert_eq!(game_state(&app), GameState::Playing);
+    assert_eq!(query.iter(&app.world).count(), initial_entities);
+    assert_eq!(crow_translation(&mut app), Vec3::new(0.0, 150.0, 1.0));
+    assert_eq!(crow_score(&mut app), 0);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use bevy::app::Events;
+use bevy::prelude::*;
+use bevy::window::WindowId;
+use common::{crow_translation, game_state, headless_app, run_ticks};
+use crow_jewels::{
+    format_date, Collider, ColliderType, Crow, GameState, Health, HighScoreEntry, HighScores,
+    PendingHighScore, MAX_HIGH_SCORES,
+};
+
+fn entry(name: &str, score: usize) -> HighScoreEntry {
+    HighScoreEntry {
+        name: name.to_string(),
+        score,
+        duration: 60.0,
+        level: "level1".to_string(),
+        date: 0,
+    }
+}
+
+fn press(app: &mut App, key: KeyCode) {
+    let mut input = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    *input = Input::default();
+    input.press(key);
+}
+
+#[test]
+fn the_table_keeps_the_best_runs_highest_first() {
+    let mut high_scores = HighScores::default();
+    assert!(!high_scores.qualifies(0));
+    for score in 1..=MAX_HIGH_SCORES {
+        assert!(high_scores.insert(entry("low", score)).is_some());
+    }
+    assert_eq!(high_scores.entries.len(), MAX_HIGH_SCORES);
+    assert_eq!(high_scores.entries[0].score, MAX_HIGH_SCORES);
+
+    // a full table only takes runs that beat its last place
+    assert!(!high_scores.qualifies(1));
+    assert_eq!(high_scores.insert(entry("tie", MAX_HIGH_SCORES)), Some(1));
+    assert_eq!(high_scores.insert(entry("best", 100)), Some(0));
+    assert_eq!(high_scores.entries.len(), MAX_HIGH_SCORES);
+    assert_eq!(high_scores.entries[0].name, "best");
+    assert_eq!(high_scores.entries[1].name, "low");
+    assert_eq!(high_scores.entries[2].name, "tie");
+    assert_eq!(high_scores.entries.last().unwrap().score, 3);
+}
+
+#[test]
+fn high_scores_are_saved_to_and_loaded_from_disk() {
+    let dir = std::env::temp_dir().join(format!("crow_jewels_highscores_{}", std::process::id()));
+    let path = dir.join("nested").join("highscores.ron");
+    assert_eq!(HighScores::load(&path).unwrap(), HighScores::default());
+
+    let mut high_scores = HighScores::default();
+    high_scores.insert(entry("Ada", 12));
+    high_scores.insert(entry("Bo", 7));
+    high_scores.save(&path).unwrap();
+    assert_eq!(HighScores::load(&path).unwrap(), high_scores);
+
+    std::fs::write(&path, "not ron").unwrap();
+    assert!(HighScores::load(&path).is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn dates_are_shown_as_calendar_days() {
+    assert_eq!(format_date(0), "1970-01-01");
+    assert_eq!(format_date(951_782_400), "2000-02-29");
+    assert_eq!(format_date(1_700_000_000), "2023-11-14");
+}
+
+#[test]
+fn a_record_run_asks_for_a_name_before_restarting() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let mut query = app.world.query::<(&mut Crow, &mut Health)>();
+    let (mut crow, mut health) = query.iter_mut(&mut app.world).next().unwrap();
+    crow.score = 15;
+    health.lives = 1;
+
+    let translation = crow_translation(&mut app);
+    app.world
+        .spawn()
+        .insert(Transform::from_translation(
+            translation + Vec3::new(50.0, 0.0, 0.0),
+        ))
+        .insert(Collider {
+            width: 64.0,
+            height: 64.0,
+            collider_type: ColliderType::Person,
+        });
+    run_ticks(&mut app, 2);
+    assert_eq!(game_state(&app), GameState::GameOver);
+
+    // typing a space does not restart the run while the name is being entered
+    let mut characters = app
+        .world
+        .get_resource_mut::<Events<ReceivedCharacter>>()
+        .unwrap();
+    for char in "Kim Ho".chars() {
+        characters.send(ReceivedCharacter {
+            id: WindowId::primary(),
+            char,
+        });
+    }
+    press(&mut app, KeyCode::Space);
+    run_ticks(&mut app, 1);
+    assert_eq!(game_state(&app), GameState::GameOver);
+    press(&mut app, KeyCode::Back);
+    run_ticks(&mut app, 1);
+    let pending = app.world.get_resource::<PendingHighScore>().unwrap();
+    assert_eq!(pending.0.as_ref().unwrap().name, "Kim H");
+
+    press(&mut app, KeyCode::Return);
+    run_ticks(&mut app, 1);
+    assert!(app
+        .world
+        .get_resource::<PendingHighScore>()
+        .unwrap()
+        .0
+        .is_none());
+    let high_scores = app.world.get_resource::<HighScores>().unwrap();
+    assert_eq!(high_scores.entries.len(), 1);
+    let record = &high_scores.entries[0];
+    assert_eq!((record.name.as_str(), record.score), ("Kim H", 15));
+    assert_eq!(record.level, "level1");
+    assert!(record.duration > 1.9 && record.date > 0);
+
+    press(&mut app, KeyCode::Space);
+    run_ticks(&mut app, 1);
+    assert_eq!(game_state(&app), GameState::Playing);
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{headless_app, menu, menu_app, tap_key};
+use crow_jewels::{Action, Actions, Bindings, BindingsFile, MenuItem, MenuScreen};
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir()
+        .join(format!("crow_jewels_{}_{}", name, std::process::id()))
+        .join("bindings.ron")
+}
+
+#[test]
+fn bindings_files_only_need_the_actions_they_change() {
+    let path = temp_path("partial_bindings");
+    assert_eq!(Bindings::load(&path).unwrap(), Bindings::default());
+
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "(actions: { Flap: (keys: [W], buttons: [North]) })").unwrap();
+    let bindings = Bindings::load(&path).unwrap();
+    let flap = bindings.get(Action::Flap).unwrap();
+    assert_eq!(flap.keys, vec![KeyCode::W]);
+    assert_eq!(flap.buttons, vec![GamepadButtonType::North]);
+    assert_eq!(
+        bindings.get(Action::MoveLeft),
+        Bindings::default().get(Action::MoveLeft)
+    );
+    assert_eq!(bindings.describe(Action::Flap), "W / (North)");
+
+    bindings.save(&path).unwrap();
+    assert_eq!(Bindings::load(&path).unwrap(), bindings);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn actions_follow_every_bound_key_and_button() {
+    let mut app = headless_app();
+    let mut input = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    input.press(KeyCode::Space);
+    app.update();
+    let actions = app.world.get_resource::<Actions>().unwrap();
+    assert!(actions.pressed(Action::Flap) && actions.just_pressed(Action::Flap));
+    assert!(!actions.pressed(Action::Interact));
+
+    let mut buttons = app
+        .world
+        .get_resource_mut::<Input<GamepadButton>>()
+        .unwrap();
+    buttons.press(GamepadButton(Gamepad(1), GamepadButtonType::South));
+    app.update();
+    let actions = app.world.get_resource::<Actions>().unwrap();
+    assert!(actions.pressed(Action::Interact) && actions.pressed(Action::Flap));
+}
+
+#[test]
+fn the_controls_page_rebinds_an_action_and_saves_it() {
+    let mut app = menu_app();
+    let path = temp_path("rebinding");
+    app.world.insert_resource(BindingsFile(Some(path.clone())));
+
+    // main menu > settings > controls
+    tap_key(&mut app, KeyCode::Down);
+    tap_key(&mut app, KeyCode::Down);
+    tap_key(&mut app, KeyCode::Return);
+    tap_key(&mut app, KeyCode::Down);
+    tap_key(&mut app, KeyCode::Return);
+    assert_eq!(menu(&app).screen, MenuScreen::Controls);
+
+    for _ in 0..4 {
+        tap_key(&mut app, KeyCode::Down);
+    }
+    assert_eq!(menu(&app).selected_item(), &MenuItem::Rebind(Action::Flap));
+    tap_key(&mut app, KeyCode::Return);
+    assert_eq!(menu(&app).rebinding, Some(Action::Flap));
+
+    // the key that is bound does not also move through the menu
+    tap_key(&mut app, KeyCode::Up);
+    assert_eq!(menu(&app).rebinding, None);
+    assert_eq!(menu(&app).selected_item(), &MenuItem::Rebind(Action::Flap));
+    let bindings = app.world.get_resource::<Bindings>().unwrap();
+    assert_eq!(bindings.get(Action::Flap).unwrap().keys, vec![KeyCode::Up]);
+    // the gamepad binding is left alone
+    assert_eq!(
+        bindings.get(Action::Flap).unwrap().buttons,
+        vec![GamepadButtonType::South]
+    );
+    assert_eq!(&Bindings::load(&path).unwrap(), bindings);
+
+    let mut input = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    input.press(KeyCode::Up);
+    app.update();
+    let actions = app.world.get_resource::<Actions>().unwrap();
+    assert!(actions.pressed(Action::Flap) && actions.pressed(Action::MoveUp));
+    let mut input = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+    *input = Input::default();
+    // [Up] is still bound to moving up as well
+    assert_eq!(
+        menu(&app).selected_item(),
+        &MenuItem::Rebind(Action::MoveDown)
+    );
+
+    // resetting brings the defaults back, on disk as well
+    tap_key(&mut app, KeyCode::Down);
+    tap_key(&mut app, KeyCode::Down);
+    tap_key(&mut app, KeyCode::Down);
+    tap_key(&mut app, KeyCode::Down);
+    tap_key(&mut app, KeyCode::Down);
+    assert_eq!(menu(&app).selected_item(), &MenuItem::ResetBindings);
+    tap_key(&mut app, KeyCode::Return);
+    assert_eq!(
+        *app.world.get_resource::<Bindings>().unwrap(),
+        Bindings::default()
+    );
+    assert_eq!(Bindings::load(&path).unwrap(), Bindings::default());
+
+    tap_key(&mut app, KeyCode::Escape);
+    assert_eq!(menu(&app).screen, MenuScreen::Settings);
+    assert_eq!(menu(&app).selected_item(), &MenuItem::Controls);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{
+    crow_translation, game_state, headless_app, menu_app, pick_level, run_ticks, tap_key,
+    wait_while_loading,
+};
+use crow_jewels::{Collider, ColliderType, CurrentLevel, GameState, Level, LevelGeometry, Surface};
+
+fn surface_count(app: &mut App) -> usize {
+    let mut query = app.world.query::<&Collider>();
+    query
+        .iter(&app.world)
+        .filter(|collider| collider.collider_type == ColliderType::Surface)
+        .count()
+}
+
+fn edit_level(app: &mut App, edit: impl FnOnce(&mut Level)) {
+    let handle = app.world.get_resource::<CurrentLevel>().unwrap().0.clone();
+    let mut levels = app.world.get_resource_mut::<Assets<Level>>().unwrap();
+    edit(levels.get_mut(&handle).unwrap());
+}
+
+#[test]
+fn shipped_level_matches_the_original_layout() {
+    let mut app = headless_app();
+    assert_eq!(surface_count(&mut app), 8);
+    let mut query = app.world.query::<&Collider>();
+    let nests = query
+        .iter(&app.world)
+        .filter(|collider| collider.collider_type == ColliderType::Nest)
+        .count();
+    assert_eq!(nests, 1);
+    assert_eq!(crow_translation(&mut app), Vec3::new(0.0, 150.0, 1.0));
+}
+
+#[test]
+fn editing_the_level_rebuilds_its_geometry_and_keeps_the_crow() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let crow = crow_translation(&mut app);
+    let mut query = app.world.query_filtered::<Entity, With<LevelGeometry>>();
+    let old_geometry: Vec<Entity> = query.iter(&app.world).collect();
+
+    edit_level(&mut app, |level| {
+        level.surfaces.push(Surface {
+            texture: "brick.png".to_string(),
+            position: Vec2::new(400.0, 16.0),
+            size: Vec2::new(32.0, 32.0),
+        })
+    });
+    run_ticks(&mut app, 2);
+
+    assert_eq!(surface_count(&mut app), 9);
+    assert!(old_geometry
+        .iter()
+        .all(|entity| app.world.get_entity(*entity).is_none()));
+    assert_eq!(crow_translation(&mut app), crow);
+}
+
+#[test]
+fn crow_is_moved_to_the_spawn_when_a_surface_appears_around_it() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+    let crow = crow_translation(&mut app);
+
+    edit_level(&mut app, |level| {
+        level.crow_spawn = Vec2::new(300.0, 150.0);
+        level.surfaces.push(Surface {
+            texture: "brick.png".to_string(),
+            position: crow.truncate(),
+            size: Vec2::new(100.0, 100.0),
+        })
+    });
+    run_ticks(&mut app, 2);
+
+    let moved = crow_translation(&mut app);
+    assert_eq!(moved.x, 300.0);
+}
+
+// Loads `path` the way picking it in the level select does.
+#[test]
+fn levels_that_fail_to_load_fall_back_to_the_last_good_one() {
+    let mut app = menu_app();
+    let good = app.world.get_resource::<CurrentLevel>().unwrap().0.clone();
+
+    let dir = std::env::temp_dir().join(format!("crow_jewels_broken_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let broken = dir.join("broken.level.ron");
+    std::fs::write(&broken, "(background: \"sky.png\", bounds: (").unwrap();
+    pick_level(&mut app, broken.to_str().unwrap());
+    wait_while_loading(&mut app);
+    assert_eq!(game_state(&app), GameState::MainMenu);
+    assert_eq!(app.world.get_resource::<CurrentLevel>().unwrap().0, good);
+
+    pick_level(&mut app, "levels/missing.level.ron");
+    wait_while_loading(&mut app);
+    assert_eq!(game_state(&app), GameState::MainMenu);
+    assert_eq!(app.world.get_resource::<CurrentLevel>().unwrap().0, good);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn levels_with_impossible_people_spawns_are_rejected() {
+    let mut app = menu_app();
+    let good = app.world.get_resource::<CurrentLevel>().unwrap().0.clone();
+
+    let dir = std::env::temp_dir().join(format!("crow_jewels_people_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let shipped = std::fs::read_to_string("assets/levels/level1.level.ron").unwrap();
+    let level = dir.join("inverted.level.ron");
+    std::fs::write(
+        &level,
+        shipped.replace("min_distance: 300.0", "min_distance: 3000.0"),
+    )
+    .unwrap();
+    pick_level(&mut app, level.to_str().unwrap());
+    wait_while_loading(&mut app);
+    assert_eq!(app.world.get_resource::<CurrentLevel>().unwrap().0, good);
+
+    // people all standing the same distance away is fine
+    let level = dir.join("fixed.level.ron");
+    std::fs::write(
+        &level,
+        shipped.replace("min_distance: 300.0", "min_distance: 1500.0"),
+    )
+    .unwrap();
+    pick_level(&mut app, level.to_str().unwrap());
+    wait_while_loading(&mut app);
+    assert_ne!(app.world.get_resource::<CurrentLevel>().unwrap().0, good);
+    tap_key(&mut app, KeyCode::Return);
+    assert_eq!(game_state(&app), GameState::Playing);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use bevy::app::{AppExit, Events};
+use bevy::prelude::*;
+use common::{
+    crow_translation, game_state, headless_app, menu, menu_app, run_ticks, script, tap_button,
+    tap_key,
+};
+use crow_jewels::{
+    Crow, CrowControls, Difficulty, GameState, Jewel, MenuItem, MenuScreen, RunEntity, RunTime,
+};
+
+fn run_entities(app: &mut App) -> usize {
+    let mut query = app.world.query_filtered::<Entity, With<RunEntity>>();
+    query.iter(&app.world).count()
+}
+
+#[test]
+fn the_game_opens_on_the_main_menu() {
+    let mut app = menu_app();
+    run_ticks(&mut app, 10);
+    assert_eq!(game_state(&app), GameState::MainMenu);
+    assert_eq!(menu(&app).screen, MenuScreen::Main);
+    assert_eq!(menu(&app).selected_item(), &MenuItem::Play);
+    assert_eq!(run_entities(&mut app), 0);
+
+    tap_key(&mut app, KeyCode::Return);
+    assert_eq!(game_state(&app), GameState::Playing);
+    let mut query = app.world.query_filtered::<Entity, With<Crow>>();
+    assert_eq!(query.iter(&app.world).count(), 1);
+}
+
+#[test]
+fn settings_change_the_difficulty_and_back_returns_to_the_main_menu() {
+    let mut app = menu_app();
+    // up from the first item wraps around to the last
+    tap_key(&mut app, KeyCode::Up);
+    assert_eq!(menu(&app).selected_item(), &MenuItem::Quit);
+    tap_key(&mut app, KeyCode::Down);
+    tap_key(&mut app, KeyCode::Down);
+    tap_key(&mut app, KeyCode::Down);
+    assert_eq!(menu(&app).selected_item(), &MenuItem::Settings);
+
+    tap_key(&mut app, KeyCode::Return);
+    assert_eq!(menu(&app).screen, MenuScreen::Settings);
+    tap_key(&mut app, KeyCode::Right);
+    assert_eq!(
+        *app.world.get_resource::<Difficulty>().unwrap(),
+        Difficulty::Hard
+    );
+    tap_key(&mut app, KeyCode::Left);
+    tap_key(&mut app, KeyCode::Left);
+    assert_eq!(
+        *app.world.get_resource::<Difficulty>().unwrap(),
+        Difficulty::Easy
+    );
+
+    tap_key(&mut app, KeyCode::Escape);
+    assert_eq!(menu(&app).screen, MenuScreen::Main);
+    assert_eq!(game_state(&app), GameState::MainMenu);
+}
+
+#[test]
+fn the_level_select_lists_the_shipped_levels() {
+    let mut app = menu_app();
+    tap_key(&mut app, KeyCode::Down);
+    tap_key(&mut app, KeyCode::Return);
+    assert_eq!(menu(&app).screen, MenuScreen::LevelSelect);
+    assert_eq!(
+        menu(&app).items,
+        vec![
+            MenuItem::Level("levels/level1.level.ron".to_string()),
+            MenuItem::Back
+        ]
+    );
+
+    // picking a level loads it and comes back to the main menu
+    tap_key(&mut app, KeyCode::Return);
+    run_ticks(&mut app, 2);
+    assert_eq!(game_state(&app), GameState::MainMenu);
+    assert_eq!(menu(&app).screen, MenuScreen::Main);
+}
+
+#[test]
+fn the_menus_work_with_a_gamepad() {
+    let mut app = menu_app();
+    tap_button(&mut app, GamepadButtonType::DPadDown);
+    tap_button(&mut app, GamepadButtonType::DPadDown);
+    tap_button(&mut app, GamepadButtonType::DPadDown);
+    tap_button(&mut app, GamepadButtonType::South);
+    assert_eq!(menu(&app).screen, MenuScreen::HighScores);
+    tap_button(&mut app, GamepadButtonType::East);
+    assert_eq!(menu(&app).screen, MenuScreen::Main);
+
+    tap_button(&mut app, GamepadButtonType::South);
+    assert_eq!(game_state(&app), GameState::Playing);
+    tap_button(&mut app, GamepadButtonType::Start);
+    assert_eq!(game_state(&app), GameState::Paused);
+    tap_button(&mut app, GamepadButtonType::Start);
+    assert_eq!(game_state(&app), GameState::Playing);
+}
+
+#[test]
+fn pausing_freezes_the_run() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 60);
+    tap_key(&mut app, KeyCode::Escape);
+    assert_eq!(game_state(&app), GameState::Paused);
+    assert_eq!(menu(&app).screen, MenuScreen::Pause);
+
+    let run_time = app.world.get_resource::<RunTime>().unwrap().0;
+    let translation = crow_translation(&mut app);
+    let mut query = app.world.query::<&Jewel>();
+    let jewels = query.iter(&app.world).count();
+    script(&mut app).hold(
+        CrowControls {
+            right: true,
+            ..Default::default()
+        },
+        600,
+    );
+    run_ticks(&mut app, 600);
+    assert_eq!(app.world.get_resource::<RunTime>().unwrap().0, run_time);
+    assert_eq!(crow_translation(&mut app), translation);
+    assert_eq!(query.iter(&app.world).count(), jewels);
+
+    // [Escape] closes the pause menu again without pausing straight away
+    tap_key(&mut app, KeyCode::Escape);
+    assert_eq!(game_state(&app), GameState::Playing);
+    run_ticks(&mut app, 10);
+    assert!(app.world.get_resource::<RunTime>().unwrap().0 > run_time);
+}
+
+#[test]
+fn leaving_a_run_from_the_pause_menu_clears_the_world() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 10);
+    tap_key(&mut app, KeyCode::Escape);
+    tap_key(&mut app, KeyCode::Down);
+    assert_eq!(menu(&app).selected_item(), &MenuItem::MainMenu);
+    tap_key(&mut app, KeyCode::Return);
+    assert_eq!(game_state(&app), GameState::MainMenu);
+    assert_eq!(menu(&app).screen, MenuScreen::Main);
+    run_ticks(&mut app, 1);
+    assert_eq!(run_entities(&mut app), 0);
+
+    // a new run starts from scratch
+    tap_key(&mut app, KeyCode::Return);
+    assert_eq!(crow_translation(&mut app), Vec3::new(0.0, 150.0, 1.0));
+}
+
+#[test]
+fn quit_closes_the_game() {
+    let mut app = menu_app();
+    tap_key(&mut app, KeyCode::Up);
+    tap_key(&mut app, KeyCode::Return);
+    let exits = app.world.get_resource::<Events<AppExit>>().unwrap();
+    assert_eq!(exits.iter_current_update_events().count(), 1);
+}
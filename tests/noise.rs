@@ -0,0 +1,93 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{app_without_people, run_ticks, script, spawn_person};
+use crow_jewels::{Crow, CrowControls, NoiseLevel, Person, PersonState};
+
+fn noise_level(app: &App) -> f32 {
+    app.world.get_resource::<NoiseLevel>().unwrap().0
+}
+
+#[test]
+fn standing_still_is_silent() {
+    let app = app_without_people();
+    assert_eq!(noise_level(&app), 0.0);
+}
+
+#[test]
+fn flaps_are_louder_than_footsteps() {
+    let mut app = app_without_people();
+    script(&mut app).hold(
+        CrowControls {
+            right: true,
+            ..Default::default()
+        },
+        10,
+    );
+    run_ticks(&mut app, 10);
+    let footsteps = noise_level(&app);
+    assert!(footsteps > 0.0);
+
+    script(&mut app).hold(
+        CrowControls {
+            flap: true,
+            ..Default::default()
+        },
+        1,
+    );
+    run_ticks(&mut app, 1);
+    assert!(noise_level(&app) > footsteps);
+
+    // the meter falls back to silence once the crow is quiet again
+    run_ticks(&mut app, 180);
+    assert_eq!(noise_level(&app), 0.0);
+}
+
+#[test]
+fn people_investigate_noises_behind_them() {
+    let mut app = app_without_people();
+    // looking right, away from the crow
+    let person = spawn_person(&mut app, Vec2::new(300.0, 32.0), Vec::new());
+    run_ticks(&mut app, 30);
+    assert_eq!(
+        app.world.get::<Person>(person).unwrap().state,
+        PersonState::Idle
+    );
+
+    script(&mut app).hold(
+        CrowControls {
+            flap: true,
+            ..Default::default()
+        },
+        1,
+    );
+    run_ticks(&mut app, 2);
+    let heard = app.world.get::<Person>(person).unwrap();
+    assert_eq!(heard.state, PersonState::Investigate);
+    assert_eq!(heard.facing, -1.0);
+    assert!(app.world.get::<Transform>(person).unwrap().translation.x < 300.0);
+}
+
+#[test]
+fn diving_is_as_loud_as_flapping_for_as_long_as_it_lasts() {
+    let mut app = app_without_people();
+    let mut query = app.world.query_filtered::<&mut Transform, With<Crow>>();
+    query.iter_mut(&mut app.world).next().unwrap().translation.y += 400.0;
+    // the crow falls for a second before diving, quietly
+    run_ticks(&mut app, 30);
+    assert_eq!(noise_level(&app), 0.0);
+
+    script(&mut app).hold(
+        CrowControls {
+            dive: true,
+            ..Default::default()
+        },
+        1,
+    );
+    run_ticks(&mut app, 1);
+    let start = noise_level(&app);
+    assert!(start > 0.8);
+    // still rushing down a while later
+    run_ticks(&mut app, 12);
+    assert!(noise_level(&app) > 0.8);
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{app_without_people, crow_translation, run_ticks, spawn_person, spawn_surface};
+use crow_jewels::{Contacts, Crow, KinematicBody, Person, PersonState};
+
+fn person_state(app: &App, person: Entity) -> PersonState {
+    app.world.get::<Person>(person).unwrap().state
+}
+
+fn person_x(app: &App, person: Entity) -> f32 {
+    person_translation(app, person).x
+}
+
+fn person_translation(app: &App, person: Entity) -> Vec3 {
+    app.world.get::<Transform>(person).unwrap().translation
+}
+
+#[test]
+fn people_grow_suspicious_then_chase_the_crow() {
+    let mut app = app_without_people();
+    // looking right, towards the crow standing on the big brick
+    let person = spawn_person(&mut app, Vec2::new(-250.0, 20.0), Vec::new());
+
+    run_ticks(&mut app, 30);
+    assert_eq!(person_state(&app, person), PersonState::Suspicious);
+    assert_eq!(person_x(&app, person), -250.0);
+
+    run_ticks(&mut app, 60);
+    assert_eq!(person_state(&app, person), PersonState::Chase);
+    assert!(person_x(&app, person) > -250.0);
+}
+
+#[test]
+fn people_do_not_see_behind_them() {
+    let mut app = app_without_people();
+    let person = spawn_person(&mut app, Vec2::new(250.0, 20.0), Vec::new());
+    run_ticks(&mut app, 120);
+    assert_eq!(person_state(&app, person), PersonState::Idle);
+}
+
+#[test]
+fn a_crow_high_in_the_sky_goes_unnoticed() {
+    let mut app = app_without_people();
+    let mut query = app
+        .world
+        .query_filtered::<(&mut Transform, &mut KinematicBody, &mut Contacts), With<Crow>>();
+    let (mut transform, mut body, mut contacts) = query.iter_mut(&mut app.world).next().unwrap();
+    transform.translation.y = 500.0;
+    body.gravity = 0.0;
+    *contacts = Contacts::default();
+
+    let person = spawn_person(&mut app, Vec2::new(-250.0, 20.0), Vec::new());
+    run_ticks(&mut app, 120);
+    assert_eq!(crow_translation(&mut app).y, 500.0);
+    assert_eq!(person_state(&app, person), PersonState::Idle);
+}
+
+#[test]
+fn surfaces_block_line_of_sight() {
+    let mut app = app_without_people();
+    spawn_surface(&mut app, Vec2::new(-150.0, 100.0), Vec2::new(32.0, 200.0));
+
+    let person = spawn_person(&mut app, Vec2::new(-250.0, 20.0), Vec::new());
+    run_ticks(&mut app, 120);
+    assert_eq!(person_state(&app, person), PersonState::Idle);
+}
+
+#[test]
+fn people_patrol_between_their_waypoints() {
+    let mut app = app_without_people();
+    // far enough from the crow that it stays out of sight, between the two vines
+    let person = spawn_person(
+        &mut app,
+        Vec2::new(-950.0, 32.0),
+        vec![Vec2::new(-1000.0, 32.0), Vec2::new(-900.0, 32.0)],
+    );
+
+    let mut min_x = f32::INFINITY;
+    let mut max_x = f32::NEG_INFINITY;
+    for _ in 0..900 {
+        run_ticks(&mut app, 1);
+        min_x = min_x.min(person_x(&app, person));
+        max_x = max_x.max(person_x(&app, person));
+        assert!(matches!(
+            person_state(&app, person),
+            PersonState::Idle | PersonState::Patrol
+        ));
+    }
+    assert!(min_x <= -998.0, "reached {}", min_x);
+    assert!(max_x >= -902.0, "reached {}", max_x);
+}
+
+#[test]
+fn people_go_back_after_losing_the_crow() {
+    let mut app = app_without_people();
+    let person = spawn_person(&mut app, Vec2::new(-250.0, 20.0), Vec::new());
+    run_ticks(&mut app, 90);
+    assert_eq!(person_state(&app, person), PersonState::Chase);
+
+    // the crow disappears far off to the right
+    let mut query = app.world.query_filtered::<&mut Transform, With<Crow>>();
+    query.iter_mut(&mut app.world).next().unwrap().translation.x = 1400.0;
+    run_ticks(&mut app, 240);
+    assert_eq!(person_state(&app, person), PersonState::Return);
+
+    run_ticks(&mut app, 600);
+    assert_eq!(person_state(&app, person), PersonState::Idle);
+    assert!((person_x(&app, person) - -250.0).abs() <= 2.0);
+}
+
+#[test]
+fn people_stand_on_the_floor() {
+    let mut app = app_without_people();
+    // half sunk into the floor, whose top is at y = 0
+    let person = spawn_person(&mut app, Vec2::new(-750.0, 20.0), Vec::new());
+    run_ticks(&mut app, 30);
+    assert_eq!(person_translation(&app, person).y, 32.0);
+    let contacts = app.world.get::<Contacts>(person).unwrap();
+    assert!(contacts.grounded);
+}
+
+#[test]
+fn patrols_turn_around_at_walls() {
+    let mut app = app_without_people();
+    // the wall's left side is at x = -784, well short of the far waypoint
+    spawn_surface(&mut app, Vec2::new(-768.0, 48.0), Vec2::new(32.0, 96.0));
+    let person = spawn_person(
+        &mut app,
+        Vec2::new(-900.0, 32.0),
+        vec![Vec2::new(-700.0, 32.0), Vec2::new(-1000.0, 32.0)],
+    );
+
+    let mut max_x = f32::NEG_INFINITY;
+    for _ in 0..900 {
+        run_ticks(&mut app, 1);
+        max_x = max_x.max(person_x(&app, person));
+    }
+    assert!(max_x <= -816.0 + 0.01, "walked to {}", max_x);
+    assert!(person_x(&app, person) < -816.0 - 20.0);
+}
+
+#[test]
+fn patrols_turn_around_at_ledges() {
+    let mut app = app_without_people();
+    // a platform from x = -1000 to -800 with its top at y = 300
+    spawn_surface(&mut app, Vec2::new(-900.0, 290.0), Vec2::new(200.0, 20.0));
+    let person = spawn_person(
+        &mut app,
+        Vec2::new(-900.0, 332.0),
+        vec![Vec2::new(-600.0, 332.0), Vec2::new(-1200.0, 332.0)],
+    );
+
+    for _ in 0..900 {
+        run_ticks(&mut app, 1);
+        let translation = person_translation(&app, person);
+        assert_eq!(translation.y, 332.0);
+        assert!(translation.x > -1000.0 && translation.x < -800.0);
+    }
+}
+
+#[test]
+fn people_do_not_stack_on_top_of_each_other() {
+    let mut app = app_without_people();
+    let first = spawn_person(&mut app, Vec2::new(-750.0, 32.0), Vec::new());
+    let second = spawn_person(&mut app, Vec2::new(-740.0, 32.0), Vec::new());
+    run_ticks(&mut app, 30);
+    let distance = (person_x(&app, first) - person_x(&app, second)).abs();
+    assert!(distance >= 63.9, "only {} apart", distance);
+}
+
+#[test]
+fn people_pushed_apart_are_not_pushed_into_walls() {
+    let mut app = app_without_people();
+    // the wall's left side is at x = -784, the second person is right up against it
+    spawn_surface(&mut app, Vec2::new(-768.0, 48.0), Vec2::new(32.0, 96.0));
+    let first = spawn_person(&mut app, Vec2::new(-830.0, 32.0), Vec::new());
+    let second = spawn_person(&mut app, Vec2::new(-816.0, 32.0), Vec::new());
+    for _ in 0..30 {
+        run_ticks(&mut app, 1);
+        assert!(person_x(&app, second) <= -816.0 + 0.01);
+    }
+    let distance = (person_x(&app, first) - person_x(&app, second)).abs();
+    assert!(distance >= 63.9, "only {} apart", distance);
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{crow_translation, headless_app, run_ticks, script, spawn_surface};
+use crow_jewels::{
+    sweep, Collider, ColliderType, Contacts, Crow, CrowControls, KinematicBody, SpatialHash,
+    SweepHit,
+};
+
+fn crow_contacts(app: &mut App) -> Contacts {
+    let mut query = app.world.query_filtered::<&Contacts, With<Crow>>();
+    *query.iter(&app.world).next().unwrap()
+}
+
+#[test]
+fn resting_crow_is_grounded() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+
+    assert_eq!(crow_translation(&mut app).y, 130.0);
+    assert_eq!(
+        crow_contacts(&mut app),
+        Contacts {
+            grounded: true,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn walls_push_the_crow_out_and_keep_it_grounded() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+
+    // the wall's right side is at x = -48, the crow is 60 wide
+    let start = crow_translation(&mut app);
+    spawn_surface(
+        &mut app,
+        start.truncate() + Vec2::new(-80.0, 0.0),
+        Vec2::new(64.0, 64.0),
+    );
+    script(&mut app).hold(
+        CrowControls {
+            left: true,
+            ..Default::default()
+        },
+        60,
+    );
+    run_ticks(&mut app, 60);
+
+    let translation = crow_translation(&mut app);
+    assert!(
+        (translation.x - -18.0).abs() < 0.01,
+        "crow at {}",
+        translation
+    );
+    assert_eq!(translation.y, start.y);
+    let contacts = crow_contacts(&mut app);
+    assert!(contacts.grounded && contacts.wall_left);
+    assert!(!contacts.wall_right && !contacts.ceiling);
+}
+
+#[test]
+fn ceilings_stop_the_crow_from_flapping_through() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+
+    // the ceiling's underside is at y = 234, the crow is 60 high
+    let start = crow_translation(&mut app);
+    spawn_surface(
+        &mut app,
+        start.truncate() + Vec2::new(0.0, 120.0),
+        Vec2::new(200.0, 32.0),
+    );
+    script(&mut app).hold(
+        CrowControls {
+            flap: true,
+            ..Default::default()
+        },
+        30,
+    );
+    let mut hit_the_ceiling = false;
+    for _ in 0..30 {
+        run_ticks(&mut app, 1);
+        assert!(crow_translation(&mut app).y <= 204.0);
+        hit_the_ceiling |= crow_contacts(&mut app).ceiling;
+    }
+    assert!(hit_the_ceiling);
+}
+
+#[test]
+fn fast_falls_do_not_tunnel_through_thin_surfaces() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 120);
+
+    // at 12000 px/s the crow moves 200 px per tick, far more than the platform and the crow
+    // are high together, so a tick before and a tick after the platform never overlap it
+    spawn_surface(&mut app, Vec2::new(0.0, 300.0), Vec2::new(200.0, 4.0));
+    let mut query = app
+        .world
+        .query_filtered::<(&mut Transform, &mut KinematicBody, &mut Contacts), With<Crow>>();
+    let (mut transform, mut body, mut contacts) = query.iter_mut(&mut app.world).next().unwrap();
+    transform.translation.y = 400.0;
+    *contacts = Contacts::default();
+    body.terminal_velocity = 20000.0;
+    body.velocity.y = -12000.0;
+    run_ticks(&mut app, 3);
+
+    assert_eq!(crow_translation(&mut app).y, 332.0);
+    assert!(crow_contacts(&mut app).grounded);
+}
+
+#[test]
+fn sweeps_report_when_and_where_boxes_meet() {
+    let hit = sweep(
+        Vec2::ZERO,
+        Vec2::new(10.0, 10.0),
+        Vec2::new(40.0, 0.0),
+        Vec2::new(30.0, 0.0),
+        Vec2::new(10.0, 10.0),
+    );
+    assert_eq!(
+        hit,
+        Some(SweepHit {
+            time: 0.5,
+            normal: Vec2::new(-1.0, 0.0),
+        })
+    );
+
+    // sliding along a side or moving away is not a hit
+    let along = sweep(
+        Vec2::ZERO,
+        Vec2::new(10.0, 10.0),
+        Vec2::new(40.0, 0.0),
+        Vec2::new(20.0, 10.0),
+        Vec2::new(10.0, 10.0),
+    );
+    assert_eq!(along, None);
+    let away = sweep(
+        Vec2::ZERO,
+        Vec2::new(10.0, 10.0),
+        Vec2::new(-40.0, 0.0),
+        Vec2::new(30.0, 0.0),
+        Vec2::new(10.0, 10.0),
+    );
+    assert_eq!(away, None);
+}
+
+#[test]
+fn spatial_hash_follows_colliders_around() {
+    let mut app = headless_app();
+    run_ticks(&mut app, 1);
+    let colliders = app.world.get_resource::<SpatialHash>().unwrap().len();
+
+    let entity = app
+        .world
+        .spawn()
+        .insert(Transform::from_xyz(5000.0, 5000.0, 0.0))
+        .insert(Collider {
+            width: 64.0,
+            height: 64.0,
+            collider_type: ColliderType::Jewel,
+        })
+        .id();
+    run_ticks(&mut app, 1);
+    let spatial_hash = app.world.get_resource::<SpatialHash>().unwrap();
+    assert_eq!(spatial_hash.len(), colliders + 1);
+    assert_eq!(
+        spatial_hash.query(Vec2::new(5000.0, 5000.0), Vec2::ONE),
+        vec![entity]
+    );
+
+    app.world
+        .get_mut::<Transform>(entity)
+        .unwrap()
+        .translation
+        .x = -5000.0;
+    run_ticks(&mut app, 1);
+    let spatial_hash = app.world.get_resource::<SpatialHash>().unwrap();
+    assert!(spatial_hash
+        .query(Vec2::new(5000.0, 5000.0), Vec2::ONE)
+        .is_empty());
+    assert_eq!(
+        spatial_hash.query(Vec2::new(-5000.0, 5000.0), Vec2::ONE),
+        vec![entity]
+    );
+
+    app.world.despawn(entity);
+    run_ticks(&mut app, 1);
+    assert_eq!(
+        app.world.get_resource::<SpatialHash>().unwrap().len(),
+        colliders
+    );
+}